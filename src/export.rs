@@ -0,0 +1,175 @@
+use std::{
+    collections::{BTreeSet, HashMap},
+    fmt::Write,
+};
+
+use crate::{
+    Order, PID,
+    math::{Number, Vector, stats::bounds},
+};
+
+/// Styling for [`to_svg`].
+#[derive(Debug, Clone, Copy)]
+pub struct SvgOptions {
+    pub stroke_width: Number,
+    pub point_radius: Number,
+    /// Space around the bounding box of the points.
+    pub margin: Number,
+}
+impl Default for SvgOptions {
+    fn default() -> Self {
+        Self {
+            stroke_width: 0.1,
+            point_radius: 0.2,
+            margin: 1.0,
+        }
+    }
+}
+
+/// Pairs of points a quantity measures between, each listed once.
+fn connections(order: &Order) -> BTreeSet<(PID, PID)> {
+    order
+        .order
+        .iter()
+        .enumerate()
+        .flat_map(|(i, quantities)| {
+            quantities
+                .iter()
+                .flat_map(|q| q.measured.iter())
+                .filter(move |&&p| p != i)
+                .map(move |&p| (p.min(i), p.max(i)))
+        })
+        .collect()
+}
+
+/// Draw a solved figure as SVG.
+///
+/// Points are circles and each pair of points a quantity measures
+/// between is a line. Y points up.
+pub fn to_svg(order: &Order, positions: &[Vector], opts: SvgOptions) -> String {
+    let flip = |p: Vector| Vector { x: p.x, y: -p.y };
+    let flipped: Vec<Vector> = positions.iter().map(|&p| flip(p)).collect();
+    let (min, size) = match bounds(&flipped) {
+        Some(b) => (b.min, b.max - b.min),
+        None => (Vector::ZERO, Vector::ZERO),
+    };
+    let mut svg = String::new();
+    let _ = writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="{} {} {} {}">"#,
+        min.x - opts.margin,
+        min.y - opts.margin,
+        size.x + opts.margin * 2.,
+        size.y + opts.margin * 2.,
+    );
+    for (a, b) in connections(order) {
+        let (a, b) = (flip(positions[a]), flip(positions[b]));
+        let _ = writeln!(
+            svg,
+            r#"<line x1="{}" y1="{}" x2="{}" y2="{}" stroke="black" stroke-width="{}"/>"#,
+            a.x, a.y, b.x, b.y, opts.stroke_width,
+        );
+    }
+    for &p in positions {
+        let p = flip(p);
+        let _ = writeln!(
+            svg,
+            r#"<circle cx="{}" cy="{}" r="{}"/>"#,
+            p.x, p.y, opts.point_radius,
+        );
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// Draw a solved figure as a minimal ASCII DXF.
+///
+/// Only an `ENTITIES` section is written, with a `POINT` per point and a
+/// `LINE` per pair of points a quantity measures between.
+pub fn to_dxf(order: &Order, positions: &[Vector]) -> String {
+    let mut dxf = String::new();
+    let _ = write!(dxf, "0\nSECTION\n2\nENTITIES\n");
+    for (a, b) in connections(order) {
+        let (a, b) = (positions[a], positions[b]);
+        let _ = write!(
+            dxf,
+            "0\nLINE\n8\n0\n10\n{}\n20\n{}\n11\n{}\n21\n{}\n",
+            a.x, a.y, b.x, b.y,
+        );
+    }
+    for &p in positions {
+        let _ = write!(dxf, "0\nPOINT\n8\n0\n10\n{}\n20\n{}\n", p.x, p.y);
+    }
+    dxf.push_str("0\nENDSEC\n0\nEOF\n");
+    dxf
+}
+
+/// One `name: (x, y)` line per point.
+///
+/// Points without a name are called `P{index}`.
+pub fn format_solution(positions: &[Vector], names: Option<&HashMap<PID, String>>) -> String {
+    let mut out = String::new();
+    for (pid, p) in positions.iter().enumerate() {
+        let _ = match names.and_then(|names| names.get(&pid)) {
+            Some(name) => writeln!(out, "{name}: {p}"),
+            None => writeln!(out, "P{pid}: {p}"),
+        };
+    }
+    out
+}
+
+#[test]
+fn rect_svg() {
+    let (fig, _) = crate::order::rectangle(10., 5.);
+    let positions = fig.solve().unwrap();
+    let svg = to_svg(&fig, &positions, SvgOptions::default());
+    assert_eq!(svg.matches("<circle").count(), 4);
+    assert_eq!(svg.matches("<line").count(), 4);
+}
+
+#[test]
+fn reference_edges() {
+    use crate::order::{base, distance, orientation, parallel};
+
+    let (mut fig, [a, b]) = base(10.);
+    let c = fig.add_point(vec![distance(a, 5.), orientation(a, 1.)]);
+    // Only the distance from `c` is drawn, not the reference segment.
+    fig.add_point(vec![distance(c, 10.), parallel(c, a, b)]);
+    assert_eq!(
+        connections(&fig).into_iter().collect::<Vec<_>>(),
+        vec![(0, 1), (0, 2), (2, 3)]
+    );
+}
+
+#[test]
+fn rect_dxf() {
+    let (fig, _) = crate::order::rectangle(10., 5.);
+    let positions = fig.solve().unwrap();
+    let dxf = to_dxf(&fig, &positions);
+    // Group codes and values alternate, one per line.
+    assert_eq!(dxf.lines().count() % 2, 0);
+    let codes: Vec<&str> = dxf.lines().step_by(2).collect();
+    let values: Vec<&str> = dxf.lines().skip(1).step_by(2).collect();
+    assert_eq!(values.iter().filter(|&&v| v == "LINE").count(), 4);
+    assert_eq!(values.iter().filter(|&&v| v == "POINT").count(), 4);
+    let count = |code| codes.iter().filter(|&&c| c == code).count();
+    assert_eq!((count("10"), count("20")), (8, 8));
+    assert_eq!((count("11"), count("21")), (4, 4));
+    assert_eq!(values.last(), Some(&"EOF"));
+}
+
+#[test]
+fn rect_solution() {
+    use crate::math::consts::PI;
+    use crate::order::{base, distance, orientation};
+
+    let (mut fig, [a, b]) = base(10.);
+    let c = fig.add_point(vec![distance(b, 5.), orientation(b, PI / 2.)]);
+    fig.add_point(vec![distance(c, 10.), orientation(c, PI)]);
+    let positions = fig.solve().unwrap();
+    let names = HashMap::from([(a, "A".to_string()), (b, "B".to_string())]);
+    assert_eq!(
+        format_solution(&positions, Some(&names)),
+        "A: (0.00, 0.00)\nB: (10.00, 0.00)\nP2: (10.00, 5.00)\nP3: (0.00, 5.00)\n"
+    );
+}
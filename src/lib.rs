@@ -1,8 +1,32 @@
 // #![deny(missing_docs)]
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+#[cfg(not(any(feature = "std", feature = "libm")))]
+compile_error!("without the `std` feature, enable `libm` for float math");
+
+#[cfg(feature = "std")]
+#[allow(missing_docs)]
+pub mod export;
 
 #[allow(missing_docs)]
 pub mod math;
 
+#[cfg(feature = "std")]
 #[allow(missing_docs)]
 pub mod order;
-pub use order::{Order, PID};
\ No newline at end of file
+
+pub mod prelude;
+
+#[cfg(feature = "std")]
+pub use order::Order;
+
+/// Internal point IDs.
+///
+/// Currently [`usize`].
+pub type PID = usize;
+
+/// A quantity by the point it places and its index among that point's
+/// quantities.
+pub type QID = (PID, usize);
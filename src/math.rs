@@ -1,8 +1,17 @@
+mod aabb;
 pub(crate) mod geo;
-mod quantity;
+mod number;
+pub mod stats;
+mod transform;
 mod vector;
 
-pub(crate) use quantity::AboutEq;
-pub use quantity::Number;
+pub use aabb::Aabb;
+#[cfg(not(any(feature = "std", test)))]
+pub(crate) use number::Float;
+pub use number::{AboutEq, Number, Tolerance, consts, epsilon};
+pub use transform::Transform;
 pub use vector::Vector;
-pub use geo::Geo;
\ No newline at end of file
+pub use geo::{
+    Geo, bounding_box, closest_point, common_tangents, contains, contains_within, geo_about_eq,
+    geo_about_eq_within, intersect, intersect_within, ray_ray_params, transform,
+};
\ No newline at end of file
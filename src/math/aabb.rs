@@ -0,0 +1,53 @@
+use super::{Tolerance, Vector};
+
+/// Axis-aligned bounding box.
+#[allow(missing_docs)]
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Aabb {
+    pub min: Vector,
+    pub max: Vector,
+}
+
+impl Aabb {
+    /// Smallest box containing both.
+    pub fn union(self, rhs: Self) -> Aabb {
+        Aabb {
+            min: Vector {
+                x: self.min.x.min(rhs.min.x),
+                y: self.min.y.min(rhs.min.y),
+            },
+            max: Vector {
+                x: self.max.x.max(rhs.max.x),
+                y: self.max.y.max(rhs.max.y),
+            },
+        }
+    }
+    /// Whether the boxes touch once each is grown by `tol`.
+    pub fn overlaps(self, rhs: Self, tol: Tolerance) -> bool {
+        self.min.x - tol.0 <= rhs.max.x + tol.0
+            && rhs.min.x - tol.0 <= self.max.x + tol.0
+            && self.min.y - tol.0 <= rhs.max.y + tol.0
+            && rhs.min.y - tol.0 <= self.max.y + tol.0
+    }
+    /// Whether `p` is inside or on the edge.
+    pub fn contains(self, p: Vector) -> bool {
+        self.min.x <= p.x && p.x <= self.max.x && self.min.y <= p.y && p.y <= self.max.y
+    }
+}
+
+#[test]
+fn union_contains() {
+    let a = Aabb {
+        min: Vector::ZERO,
+        max: Vector { x: 1., y: 1. },
+    };
+    let b = Aabb {
+        min: Vector { x: 2., y: -1. },
+        max: Vector { x: 3., y: 0. },
+    };
+    let u = a.union(b);
+    assert_eq!(u.min, Vector { x: 0., y: -1. });
+    assert_eq!(u.max, Vector { x: 3., y: 1. });
+    assert!(u.contains(Vector { x: 1.5, y: 0.5 }));
+    assert!(!a.contains(Vector { x: 1.5, y: 0.5 }));
+}
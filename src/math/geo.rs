@@ -1,36 +1,291 @@
+use alloc::{vec, vec::Vec};
+
 use crate::math::vector::Vector;
 use itertools::Itertools;
 
-use super::{AboutEq, Number};
+#[cfg(not(any(feature = "std", test)))]
+use super::Float;
+use super::{Aabb, AboutEq, Number, Tolerance, Transform, consts::TAU};
 
 #[derive(Debug, PartialEq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Geo {
     Point(Vector),
     Ray(Vector, Vector),
+    Line(Vector, Vector),
+    Segment(Vector, Vector),
     Circle(Vector, Number),
-    // All,
+    /// Every point `p` where `(p - o).dot(n) >= 0`.
+    ///
+    /// `n` is a unit normal pointing into the region.
+    HalfPlane { o: Vector, n: Vector },
+    /// The part of the circle about `c` with radius `r` counterclockwise
+    /// from angle `start` to `end`.
+    Arc { c: Vector, r: Number, start: Number, end: Number },
+    /// Everywhere, for a point with nothing to place it.
+    All,
 }
 
 fn close_on_ray(o: Vector, v: Vector, p: Vector) -> Vector {
-    let t = (p - o).dot(v).max(0.);
+    let len_sq = v.mag_squared();
+    if len_sq == 0.0 {
+        // The ray has no direction.
+        return o;
+    }
+    let t = ((p - o).dot(v) / len_sq).max(0.);
+    along_line(o, v, t)
+}
+
+fn close_on_line(o: Vector, v: Vector, p: Vector) -> Vector {
+    let len_sq = v.mag_squared();
+    if len_sq == 0.0 {
+        // The line has no direction.
+        return o;
+    }
+    let t = (p - o).dot(v) / len_sq;
     along_line(o, v, t)
 }
 
+fn close_on_segment(a: Vector, b: Vector, p: Vector) -> Vector {
+    let v = b - a;
+    let len_sq = v.mag_squared();
+    if len_sq == 0.0 {
+        // The segment is a single point.
+        return a;
+    }
+    let t = ((p - a).dot(v) / len_sq).clamp(0., 1.);
+    along_line(a, v, t)
+}
+
 fn along_line(o: Vector, v: Vector, t: Number) -> Vector {
     o + v * t
 }
 
-pub(crate) fn meet(g0: Vec<Geo>, g1: Vec<Geo>) -> Vec<Geo> {
-    g0.iter()
+/// Parameters `(t0, t1)` where `o0 + v0 * t0 == o1 + v1 * t1`.
+///
+/// Not clamped, so negative values are behind a ray's start. [`None`] if
+/// the rays are parallel.
+pub fn ray_ray_params(
+    o0: Vector,
+    v0: Vector,
+    o1: Vector,
+    v1: Vector,
+) -> Option<(Number, Number)> {
+    // https://math.stackexchange.com/a/406895
+    let b = o1 - o0;
+    // Using Cramer's Rule
+    let a = Vector { x: v0.x, y: -v1.x }.cross(Vector { x: v0.y, y: -v1.y });
+    if a == 0.0 {
+        // The lines are parallel.
+        return None;
+    }
+    let t0 = Vector { x: b.x, y: -v1.x }.cross(Vector { x: b.y, y: -v1.y }) / a;
+    let t1 = Vector { x: v0.x, y: b.x }.cross(Vector { x: v0.y, y: b.y }) / a;
+    Some((t0, t1))
+}
+
+fn line_circle(o: Vector, v: Vector, c: Vector, r: Number, tol: Tolerance) -> Vec<Number> {
+    // https://w.wiki/A6Jn
+    let o_c = o - c;
+    let v_v = v.mag_squared();
+    let v_o_c = v.dot(o_c);
+    let delta = v_o_c.powi(2) - v_v * (o_c.mag_squared() - r.powi(2));
+    if delta.is_sign_negative() {
+        // No intersection.
+        vec![]
+    } else if delta.about_zero_within(tol) {
+        // The line is tangent.
+        vec![-v_o_c / v_v]
+    } else {
+        // The line passes through.
+        let sqrt_delta = delta.sqrt();
+        vec![(-v_o_c + sqrt_delta) / v_v, (-v_o_c - sqrt_delta) / v_v]
+    }
+}
+
+/// Linear geometry over the parameter range `lo..=hi` along `v` from `o`.
+fn from_range(o: Vector, v: Vector, lo: Number, hi: Number) -> Geo {
+    match (lo.is_finite(), hi.is_finite()) {
+        (false, false) => Geo::Line(o, v),
+        (true, false) => Geo::Ray(along_line(o, v, lo), v),
+        (false, true) => Geo::Ray(along_line(o, v, hi), -v),
+        (true, true) if lo == hi => Geo::Point(along_line(o, v, lo)),
+        (true, true) => Geo::Segment(along_line(o, v, lo), along_line(o, v, hi)),
+    }
+}
+
+/// Clip linear geometry over `lo..=hi` to a half-plane.
+fn clip_linear(
+    o: Vector,
+    v: Vector,
+    lo: Number,
+    hi: Number,
+    h_o: Vector,
+    h_n: Vector,
+    tol: Tolerance,
+) -> Vec<Geo> {
+    let s0 = (o - h_o).dot(h_n);
+    let sv = v.dot(h_n);
+    let (lo, hi) = if sv.about_zero_within(tol) {
+        if s0 < -tol.0 {
+            // Parallel and outside.
+            return vec![];
+        }
+        (lo, hi)
+    } else {
+        let t = -s0 / sv;
+        if sv > 0. { (lo.max(t), hi) } else { (lo, hi.min(t)) }
+    };
+    if lo > hi {
+        // Entirely outside.
+        return vec![];
+    }
+    vec![from_range(o, v, lo, hi)]
+}
+
+/// Clip circular geometry `g` from `start` to `end` to a half-plane.
+#[allow(clippy::too_many_arguments)]
+fn clip_circular(
+    g: Geo,
+    c: Vector,
+    r: Number,
+    start: Number,
+    end: Number,
+    h_o: Vector,
+    h_n: Vector,
+    tol: Tolerance,
+) -> Vec<Geo> {
+    let s = (c - h_o).dot(h_n);
+    if s < -r - tol.0 {
+        // Entirely outside.
+        return vec![];
+    }
+    if s >= r {
+        // Entirely inside.
+        return vec![g];
+    }
+    if s.about_eq_within(-r, tol) {
+        // Touching the boundary from outside.
+        let p = c + h_n * r;
+        if !on_arc(c, r, start, end, p, tol) {
+            return vec![];
+        }
+        return vec![Geo::Point(p)];
+    }
+    // The inside spans `alpha` either side of the normal.
+    let phi = h_n.angle();
+    let alpha = (-s / r).acos();
+    let len = end - start;
+    if len >= TAU {
+        return vec![Geo::Arc { c, r, start: phi - alpha, end: phi + alpha }];
+    }
+    // Overlap with the inside, which may wrap around to either end.
+    let len = len.rem_euclid(TAU);
+    let off = (phi - alpha - start).rem_euclid(TAU);
+    [off, off - TAU]
+        .into_iter()
+        .filter_map(|o| {
+            let lo = o.max(0.);
+            let hi = (o + 2. * alpha).min(len);
+            (lo <= hi).then_some(Geo::Arc { c, r, start: start + lo, end: start + hi })
+        })
+        .collect()
+}
+
+fn on_segment(t: Number) -> bool {
+    (0.0..=1.0).contains(&t)
+}
+
+/// Whether `angle` is counterclockwise from `start` to `end`, give or take
+/// `slack`.
+fn in_span(angle: Number, start: Number, end: Number, slack: Number) -> bool {
+    if end - start >= TAU {
+        // The arc is a full circle.
+        return true;
+    }
+    let span = (end - start).rem_euclid(TAU);
+    let off = (angle - start).rem_euclid(TAU);
+    off <= span + slack || off >= TAU - slack
+}
+
+fn on_arc(c: Vector, r: Number, start: Number, end: Number, p: Vector, tol: Tolerance) -> bool {
+    in_span((p - c).angle(), start, end, tol.0 / r.abs())
+}
+
+fn close_on_arc(c: Vector, r: Number, start: Number, end: Number, p: Vector) -> Vector {
+    if p != c && in_span((p - c).angle(), start, end, 0.) {
+        return (p - c).unit() * r + c;
+    }
+    // The nearest point is an endpoint.
+    let a = Vector::from_angle(start) * r + c;
+    let b = Vector::from_angle(end) * r + c;
+    if p.dist_squared(a) <= p.dist_squared(b) { a } else { b }
+}
+
+// Only the solver meets geometry lists.
+#[cfg_attr(not(feature = "std"), allow(dead_code))]
+pub(crate) fn meet(g0: Vec<Geo>, g1: Vec<Geo>, tol: Tolerance) -> Vec<Geo> {
+    let met = g0
+        .iter()
         .cartesian_product(g1)
-        .map(|(&g0, g1)| intersect(g0, g1))
-        .concat()
+        .filter(|&(&g0, g1)| match (bounding_box(g0), bounding_box(g1)) {
+            // Too far apart to meet.
+            (Some(b0), Some(b1)) => b0.overlaps(b1, tol),
+            _ => true,
+        })
+        .map(|(&g0, g1)| intersect_within(g0, g1, tol))
+        .concat();
+    dedup_points(met, tol)
+}
+
+/// Collapse points within `tol` of an earlier point.
+#[cfg_attr(not(feature = "std"), allow(dead_code))]
+fn dedup_points(geos: Vec<Geo>, tol: Tolerance) -> Vec<Geo> {
+    let mut result: Vec<Geo> = Vec::with_capacity(geos.len());
+    for g in geos {
+        if let Geo::Point(p) = g
+            && result
+                .iter()
+                .any(|r| matches!(r, Geo::Point(q) if q.about_eq_within(p, tol)))
+        {
+            continue;
+        }
+        result.push(g);
+    }
+    result
+}
+
+/// Where `g0` and `g1` meet.
+///
+/// Every pair of variants is supported except two half-planes:
+/// - [`Geo::All`] leaves the other geometry as is.
+/// - A point is kept if it lies on the other geometry.
+/// - A half-plane clips rays, lines, segments, circles and arcs. Two
+///   half-planes share a region [`Geo`] can't represent, so they meet
+///   nowhere. Use each to clip the other geometry instead.
+/// - An arc meets like its full circle, keeping only points on the arc.
+/// - Every other pair meets at zero, one or two points.
+///
+/// ```
+/// use gsolve::math::{Geo, Vector, intersect};
+///
+/// let circle = Geo::Circle(Vector::ZERO, 1.);
+/// let ray = Geo::Ray(Vector::ZERO, Vector::POSX);
+/// assert_eq!(intersect(ray, circle), vec![Geo::Point(Vector::POSX)]);
+///
+/// let other = Geo::Circle(Vector { x: 1., y: 0. }, 1.);
+/// assert_eq!(intersect(circle, other).len(), 2);
+/// ```
+pub fn intersect(g0: Geo, g1: Geo) -> Vec<Geo> {
+    intersect_within(g0, g1, Tolerance::default())
 }
 
-fn intersect(g0: Geo, g1: Geo) -> Vec<Geo> {
+/// [`intersect`] within `tol`.
+pub fn intersect_within(g0: Geo, g1: Geo, tol: Tolerance) -> Vec<Geo> {
     match (g0, g1) {
+        (Geo::All, g) | (g, Geo::All) => vec![g],
         (g, Geo::Point(p)) | (Geo::Point(p), g) => {
-            if dist(p, g).about_zero() {
+            if contains_within(g, p, tol) {
                 // The point is close enough.
                 vec![Geo::Point(p)]
             } else {
@@ -38,74 +293,130 @@ fn intersect(g0: Geo, g1: Geo) -> Vec<Geo> {
                 vec![]
             }
         }
-        (Geo::Ray(o0, v0), Geo::Ray(o1, v1)) => {
-            // https://math.stackexchange.com/a/406895
-            let b = o1 - o0;
-            // Using Cramer's Rule
-            let a = Vector { x: v0.x, y: -v1.x }.cross(Vector { x: v0.y, y: -v1.y });
-            if a == 0.0 {
-                // The rays are parallel.
-                return vec![];
+        (Geo::HalfPlane { o: h_o, n: h_n }, g) | (g, Geo::HalfPlane { o: h_o, n: h_n }) => {
+            match g {
+                Geo::Ray(o, v) => clip_linear(o, v, 0., Number::INFINITY, h_o, h_n, tol),
+                Geo::Line(o, v) => {
+                    clip_linear(o, v, Number::NEG_INFINITY, Number::INFINITY, h_o, h_n, tol)
+                }
+                Geo::Segment(a, b) => clip_linear(a, b - a, 0., 1., h_o, h_n, tol),
+                Geo::Circle(c, r) => clip_circular(g, c, r, 0., TAU, h_o, h_n, tol),
+                Geo::Arc { c, r, start, end } => {
+                    clip_circular(g, c, r, start, end, h_o, h_n, tol)
+                }
+                // Wedges aren't representable, so this isn't supported.
+                Geo::HalfPlane { .. } => vec![],
+                Geo::Point(_) | Geo::All => unreachable!("handled above"),
             }
-            let t0 = Vector { x: b.x, y: -v1.x }.cross(Vector { x: b.y, y: -v1.y }) / a;
-            let t1 = Vector { x: v0.x, y: b.x }.cross(Vector { x: v0.y, y: b.y }) / a;
-            if t0 < 0. || t1 < 0. {
-                // The rays intersect before one of their starts.
-                vec![]
-            } else {
-                // The rays intersect.
-                vec![Geo::Point(along_line(o0, v0, t0))]
+        }
+        (Geo::Arc { c, r, start, end }, g) | (g, Geo::Arc { c, r, start, end }) => {
+            // Meet the full circle, then keep what lies on the arc.
+            intersect_within(Geo::Circle(c, r), g, tol)
+                .into_iter()
+                .filter(|g| match *g {
+                    Geo::Point(p) => on_arc(c, r, start, end, p, tol),
+                    _ => true,
+                })
+                .collect()
+        }
+        (Geo::Ray(o0, v0), Geo::Ray(o1, v1)) => match ray_ray_params(o0, v0, o1, v1) {
+            // The rays intersect.
+            Some((t0, t1)) if t0 >= 0. && t1 >= 0. => vec![Geo::Point(along_line(o0, v0, t0))],
+            // The rays are parallel or intersect before one of their starts.
+            _ => vec![],
+        },
+        (Geo::Line(o0, v0), Geo::Line(o1, v1)) => match ray_ray_params(o0, v0, o1, v1) {
+            // The lines intersect.
+            Some((t0, _)) => vec![Geo::Point(along_line(o0, v0, t0))],
+            // The lines are parallel.
+            None => vec![],
+        },
+        (Geo::Line(o0, v0), Geo::Ray(o1, v1)) | (Geo::Ray(o1, v1), Geo::Line(o0, v0)) => {
+            match ray_ray_params(o0, v0, o1, v1) {
+                // The ray crosses the line.
+                Some((t0, t1)) if t1 >= 0. => vec![Geo::Point(along_line(o0, v0, t0))],
+                // The ray is parallel or points away from the line.
+                _ => vec![],
             }
         }
-        (Geo::Circle(c, r), Geo::Ray(o, v)) | (Geo::Ray(o, v), Geo::Circle(c, r)) => {
-            // https://w.wiki/A6Jn
-            let o_c = o - c;
-            let v_o_c = v.dot(o_c);
-            let delta = v_o_c.powi(2) - (o_c.mag().powi(2) - r.powi(2));
-            if delta.is_sign_negative() {
-                // No intersection.
-                vec![]
-            } else if delta.about_zero() {
-                // The line is tangent.
-                vec![-v_o_c]
-            } else {
-                // The line passes through.
-                let sqrt_delta = delta.sqrt();
-                vec![-v_o_c + sqrt_delta, -v_o_c - sqrt_delta]
+        (Geo::Line(o, v), Geo::Segment(a, b)) | (Geo::Segment(a, b), Geo::Line(o, v)) => {
+            match ray_ray_params(o, v, a, b - a) {
+                // The line crosses the segment.
+                Some((t0, t1)) if on_segment(t1) => vec![Geo::Point(along_line(o, v, t0))],
+                // The line misses the segment.
+                _ => vec![],
             }
-            .into_iter()
-            .filter_map(|t| {
-                if t >= 0. {
-                    Some(Geo::Point(along_line(o, v, t)))
-                } else {
-                    None
+        }
+        (Geo::Ray(o, v), Geo::Segment(a, b)) | (Geo::Segment(a, b), Geo::Ray(o, v)) => {
+            match ray_ray_params(o, v, a, b - a) {
+                // The ray crosses the segment.
+                Some((t0, t1)) if t0 >= 0. && on_segment(t1) => {
+                    vec![Geo::Point(along_line(o, v, t0))]
                 }
-            })
-            .collect()
+                // The ray misses the segment.
+                _ => vec![],
+            }
+        }
+        (Geo::Segment(a0, b0), Geo::Segment(a1, b1)) => {
+            match ray_ray_params(a0, b0 - a0, a1, b1 - a1) {
+                // The segments cross.
+                Some((t0, t1)) if on_segment(t0) && on_segment(t1) => {
+                    vec![Geo::Point(along_line(a0, b0 - a0, t0))]
+                }
+                // The segments are parallel or cross past an endpoint.
+                _ => vec![],
+            }
+        }
+        (Geo::Circle(c, r), Geo::Ray(o, v)) | (Geo::Ray(o, v), Geo::Circle(c, r)) => {
+            line_circle(o, v, c, r, tol)
+                .into_iter()
+                .filter(|&t| t >= 0.)
+                .map(|t| Geo::Point(along_line(o, v, t)))
+                .collect()
+        }
+        (Geo::Circle(c, r), Geo::Line(o, v)) | (Geo::Line(o, v), Geo::Circle(c, r)) => {
+            line_circle(o, v, c, r, tol)
+                .into_iter()
+                .map(|t| Geo::Point(along_line(o, v, t)))
+                .collect()
+        }
+        (Geo::Circle(c, r), Geo::Segment(a, b)) | (Geo::Segment(a, b), Geo::Circle(c, r)) => {
+            line_circle(a, b - a, c, r, tol)
+                .into_iter()
+                .filter(|&t| on_segment(t))
+                .map(|t| Geo::Point(along_line(a, b - a, t)))
+                .collect()
         }
         (Geo::Circle(c0, r0), Geo::Circle(c1, r1)) => {
             // https://stackoverflow.com/a/3349134
             let (dir, d) = (c1 - c0).unit_mag();
+            // The circles are concentric.
+            if d.about_zero_within(tol) {
+                return vec![];
+            }
+            let inner = (r0 - r1).abs();
+            let outer = r0 + r1;
+            let tangent = d.about_eq_within(inner, tol) || d.about_eq_within(outer, tol);
             // One circle contains the other.
-            if d < (r0 - r1).abs() {
+            if d < inner && !tangent {
                 return vec![];
             }
             // The circles are separated.
-            if d > (r0 + r1) {
+            if d > outer && !tangent {
                 return vec![];
             }
             let a = (r0.powi(2) - r1.powi(2) + d.powi(2)) / (2.0 * d);
             let c = c0 + dir * a;
             // The circles touch at one point.
-            if d.about_eq(r0 + r1) {
+            if tangent {
                 return vec![Geo::Point(c)];
             }
-            let h = (r0.powi(2) - a.powi(2)).sqrt();
+            // Rounding can push `a` past `r0`.
+            let h = (r0.powi(2) - a.powi(2)).max(0.0).sqrt();
             let h_v = dir.perp() * h;
             // The circles overlap at two points.
             vec![Geo::Point(c + h_v), Geo::Point(c - h_v)]
         }
-        // (Geo::All, g) | (g, Geo::All) => vec![g],
     }
 }
 
@@ -113,16 +424,462 @@ pub(crate) fn dist(p: Vector, g: Geo) -> Number {
     match g {
         Geo::Point(p1) => p.dist(p1),
         Geo::Ray (o,v) => p.dist(close_on_ray(o, v, p)),
+        Geo::Line (o,v) => p.dist(close_on_line(o, v, p)),
+        Geo::Segment (a,b) => p.dist(close_on_segment(a, b, p)),
         Geo::Circle (c,r) => p.dist(c) - r,
-        // Geo::All => 0.0,
+        Geo::HalfPlane { o, n } => (-(p - o).dot(n)).max(0.),
+        Geo::Arc { c, r, start, end } => p.dist(close_on_arc(c, r, start, end, p)),
+        Geo::All => 0.0,
     }
 }
 
+/// Whether `p` lies on `g`.
+pub fn contains(g: Geo, p: Vector) -> bool {
+    contains_within(g, p, Tolerance::default())
+}
+
+/// Whether `p` lies within `tol` of `g`.
+pub fn contains_within(g: Geo, p: Vector, tol: Tolerance) -> bool {
+    dist(p, g).about_zero_within(tol)
+}
+
+/// Whether `g0` and `g1` are the same geometry, regardless of how they
+/// are represented.
+///
+/// Directions are compared by angle, not magnitude, and a line or
+/// half-plane may be given from any point on its boundary. Different
+/// variants are never equal, even if they cover the same points.
+pub fn geo_about_eq(g0: Geo, g1: Geo) -> bool {
+    geo_about_eq_within(g0, g1, Tolerance::default())
+}
+
+/// [`geo_about_eq`] within `tol`.
+pub fn geo_about_eq_within(g0: Geo, g1: Geo, tol: Tolerance) -> bool {
+    let same_dir = |v0: Vector, v1: Vector| v0.unit().about_eq_within(v1.unit(), tol);
+    let same_angle = |a0: Number, a1: Number| {
+        Vector::from_angle(a0).about_eq_within(Vector::from_angle(a1), tol)
+    };
+    match (g0, g1) {
+        (Geo::Point(p0), Geo::Point(p1)) => p0.about_eq_within(p1, tol),
+        (Geo::Ray(o0, v0), Geo::Ray(o1, v1)) => o0.about_eq_within(o1, tol) && same_dir(v0, v1),
+        (Geo::Line(o0, v0), Geo::Line(o1, v1)) => {
+            v0.unit().cross(v1.unit()).about_zero_within(tol)
+                && dist(o1, Geo::Line(o0, v0)).about_zero_within(tol)
+        }
+        (Geo::Segment(a0, b0), Geo::Segment(a1, b1)) => {
+            (a0.about_eq_within(a1, tol) && b0.about_eq_within(b1, tol))
+                || (a0.about_eq_within(b1, tol) && b0.about_eq_within(a1, tol))
+        }
+        (Geo::Circle(c0, r0), Geo::Circle(c1, r1)) => {
+            c0.about_eq_within(c1, tol) && r0.abs().about_eq_within(r1.abs(), tol)
+        }
+        (Geo::HalfPlane { o: o0, n: n0 }, Geo::HalfPlane { o: o1, n: n1 }) => {
+            same_dir(n0, n1) && (o1 - o0).dot(n0.unit()).about_zero_within(tol)
+        }
+        (
+            Geo::Arc { c: c0, r: r0, start: s0, end: e0 },
+            Geo::Arc { c: c1, r: r1, start: s1, end: e1 },
+        ) => {
+            c0.about_eq_within(c1, tol)
+                && r0.abs().about_eq_within(r1.abs(), tol)
+                && same_angle(s0, s1)
+                && same_angle(e0, e1)
+        }
+        (Geo::All, Geo::All) => true,
+        _ => false,
+    }
+}
+
+/// Lines tangent to both circles as `(point, direction)`.
+///
+/// External tangents come first, then internal ones. Separated circles
+/// have 4, touching circles 3, overlapping circles 2, internally touching
+/// circles 1 and nested or concentric circles none.
+pub fn common_tangents(c0: Vector, r0: Number, c1: Vector, r1: Number) -> Vec<(Vector, Vector)> {
+    let (dir, d) = (c1 - c0).unit_mag();
+    if d.about_zero() {
+        // The circles are concentric.
+        return vec![];
+    }
+    let mut lines = Vec::new();
+    // The normal `n` of each line satisfies `n.dot(dir) == cos`, with
+    // `c1` on the same side as `c0` for external tangents.
+    for cos in [(r1 - r0) / d, (-r1 - r0) / d] {
+        if cos.abs().about_eq(1.) {
+            // The circles touch, so the tangents meet.
+            let n = dir * cos.signum();
+            lines.push((c0 - n * r0, n.perp()));
+        } else if cos.abs() < 1. {
+            let sin = (1. - cos.powi(2)).sqrt();
+            for n in [dir * cos + dir.perp() * sin, dir * cos - dir.perp() * sin] {
+                lines.push((c0 - n * r0, n.perp()));
+            }
+        }
+    }
+    lines
+}
+
+/// Bounds of `g`, or [`None`] if it is unbounded.
+pub fn bounding_box(g: Geo) -> Option<Aabb> {
+    match g {
+        Geo::Point(p) => Some(Aabb { min: p, max: p }),
+        Geo::Ray (..) | Geo::Line (..) | Geo::HalfPlane { .. } | Geo::All => None,
+        Geo::Segment (a,b) => Some(Aabb { min: a, max: a }.union(Aabb { min: b, max: b })),
+        Geo::Circle (c,r) => Some(Aabb {
+            min: c - r.abs(),
+            max: c + r.abs(),
+        }),
+        Geo::Arc { c, r, start, end } => {
+            let at = |angle: Number| {
+                let p = Vector::from_angle(angle) * r + c;
+                Aabb { min: p, max: p }
+            };
+            // The endpoints and any extremes the arc sweeps past.
+            let quarters = (0..4)
+                .map(|k| k as Number * TAU / 4.)
+                .filter(|&angle| in_span(angle, start, end, 0.));
+            Some(
+                quarters
+                    .map(at)
+                    .fold(at(start).union(at(end)), Aabb::union),
+            )
+        }
+    }
+}
+
+/// Apply `t` to `g`.
+pub fn transform(g: Geo, t: &Transform) -> Geo {
+    match g {
+        Geo::Point(p) => Geo::Point(p.transform(t)),
+        Geo::Ray (o,v) => Geo::Ray(o.transform(t), t.apply_dir(v)),
+        Geo::Line (o,v) => Geo::Line(o.transform(t), t.apply_dir(v)),
+        Geo::Segment (a,b) => Geo::Segment(a.transform(t), b.transform(t)),
+        Geo::Circle (c,r) => Geo::Circle(c.transform(t), r * t.scale.abs()),
+        Geo::HalfPlane { o, n } => Geo::HalfPlane {
+            o: o.transform(t),
+            n: t.apply_dir(n),
+        },
+        Geo::Arc { c, r, start, end } => {
+            let start_t = t.apply_dir(Vector::from_angle(start)).angle();
+            Geo::Arc {
+                c: c.transform(t),
+                r: r * t.scale.abs(),
+                start: start_t,
+                end: start_t + (end - start),
+            }
+        }
+        Geo::All => Geo::All,
+    }
+}
+
+/// Closest point on `g` to `p`.
+pub fn closest_point(g: Geo, p: Vector) -> Vector {
+    match g {
+        Geo::Point(p1) => p1,
+        Geo::Ray (o,v) => close_on_ray(o, v, p),
+        Geo::Line (o,v) => close_on_line(o, v, p),
+        Geo::Segment (a,b) => close_on_segment(a, b, p),
+        Geo::Circle (c,r) => {
+            if p == c {
+                // Every point on the circle is equally close.
+                return Vector::POSX * r + c;
+            }
+            (p - c).unit() * r + c
+        }
+        Geo::HalfPlane { o, n } => p - n * (p - o).dot(n).min(0.),
+        Geo::Arc { c, r, start, end } => close_on_arc(c, r, start, end, p),
+        Geo::All => p,
+    }
+}
+
+#[cfg_attr(not(feature = "std"), allow(dead_code))]
 pub(crate) fn choose(g: Geo) -> Vector {
     match g {
         Geo::Point(p) => p,
         Geo::Ray (o,v) => along_line(o, v, 1.0),
+        Geo::Line (o,v) => along_line(o, v, 1.0),
+        Geo::Segment (a,b) => along_line(a, b - a, 0.5),
         Geo::Circle (c,r) => Vector::POSX * r + c,
-        // Geo::All => Vector::ZERO,
+        Geo::HalfPlane { o, .. } => o,
+        Geo::Arc { c, r, start, .. } => Vector::from_angle(start) * r + c,
+        Geo::All => Vector::ZERO,
     }
 }
+
+#[test]
+fn segments() {
+    let s0 = Geo::Segment(Vector { x: -1., y: 0. }, Vector { x: 1., y: 0. });
+    let s1 = Geo::Segment(Vector { x: 0., y: -1. }, Vector { x: 0., y: 1. });
+    assert_eq!(intersect(s0, s1), vec![Geo::Point(Vector::ZERO)]);
+
+    // Crossing would be past the end of the second segment.
+    let s2 = Geo::Segment(Vector { x: 0., y: 0.5 }, Vector { x: 0., y: 2. });
+    assert!(intersect(s0, s2).is_empty());
+}
+
+#[test]
+fn line_and_ray() {
+    let circle = Geo::Circle(Vector::ZERO, 5.);
+    let line = intersect(Geo::Line(Vector::ZERO, Vector::POSX), circle);
+    assert!(line.contains(&Geo::Point(Vector { x: 5., y: 0. })));
+    assert!(line.contains(&Geo::Point(Vector { x: -5., y: 0. })));
+    assert_eq!(line.len(), 2);
+
+    let ray = intersect(Geo::Ray(Vector::ZERO, Vector::POSX), circle);
+    assert_eq!(ray, vec![Geo::Point(Vector { x: 5., y: 0. })]);
+}
+
+#[test]
+fn tangent_circles() {
+    use crate::math::consts::PI;
+
+    let c1 = (Vector::POSX * 2.).rot(PI / 3.);
+    let result = intersect(Geo::Circle(Vector::ZERO, 1.), Geo::Circle(c1, 1.));
+    assert_eq!(result.len(), 1);
+    let Geo::Point(p) = result[0] else {
+        panic!("expected a point")
+    };
+    assert!(p.about_eq(c1 / 2.));
+}
+
+#[test]
+fn snap_to_circle() {
+    let circle = Geo::Circle(Vector { x: 1., y: 1. }, 2.);
+    let inside = closest_point(circle, Vector { x: 1., y: 2. });
+    assert!(inside.about_eq(Vector { x: 1., y: 3. }));
+    let outside = closest_point(circle, Vector { x: 5., y: 1. });
+    assert!(outside.about_eq(Vector { x: 3., y: 1. }));
+    let center = closest_point(circle, Vector { x: 1., y: 1. });
+    assert!(center.about_eq(Vector { x: 3., y: 1. }));
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn serde_round_trip() {
+    let geos = vec![
+        Geo::Point(Vector { x: 1., y: 2. }),
+        Geo::Ray(Vector::ZERO, Vector::POSX),
+        Geo::Circle(Vector::NEGY, 3.),
+    ];
+    let json = serde_json::to_string(&geos).unwrap();
+    let back: Vec<Geo> = serde_json::from_str(&json).unwrap();
+    assert_eq!(back, geos);
+}
+
+#[test]
+fn tolerance() {
+    let gap = Tolerance::default().0 * 100.;
+    let c0 = Geo::Circle(Vector::ZERO, 1.);
+    let c1 = Geo::Circle(Vector { x: 2. - gap, y: 0. }, 1.);
+    assert_eq!(intersect(c0, c1).len(), 2);
+    assert_eq!(intersect_within(c0, c1, Tolerance(gap * 10.)).len(), 1);
+}
+
+#[test]
+fn bounds() {
+    let circle = bounding_box(Geo::Circle(Vector { x: 1., y: 2. }, 3.)).unwrap();
+    assert_eq!(circle.min, Vector { x: -2., y: -1. });
+    assert_eq!(circle.max, Vector { x: 4., y: 5. });
+    let segment = bounding_box(Geo::Segment(Vector::POSX, Vector::NEGY)).unwrap();
+    assert_eq!(segment.min, Vector { x: 0., y: -1. });
+    assert_eq!(segment.max, Vector { x: 1., y: 0. });
+    assert_eq!(bounding_box(Geo::Ray(Vector::ZERO, Vector::POSX)), None);
+}
+
+#[test]
+fn meet_prunes_and_dedups() {
+    let tol = Tolerance::default();
+    let far = meet(
+        vec![Geo::Circle(Vector::ZERO, 1.)],
+        vec![Geo::Circle(Vector { x: 10., y: 0. }, 1.)],
+        tol,
+    );
+    assert!(far.is_empty());
+
+    let a = Geo::Circle(Vector::ZERO, 1.);
+    let b = Geo::Circle(Vector::POSX, 1.);
+    assert_eq!(meet(vec![a, a], vec![b], tol).len(), 2);
+}
+
+#[test]
+fn tangent_duplicates() {
+    let tol = Tolerance::default();
+    let circles = vec![
+        Geo::Circle(Vector::ZERO, 1.),
+        Geo::Circle(Vector { x: 2., y: 0. }, 1.),
+    ];
+    let line = vec![Geo::Line(Vector::POSX, Vector::POSY)];
+    assert_eq!(meet(circles, line, tol), vec![Geo::Point(Vector::POSX)]);
+}
+
+#[test]
+fn half_plane() {
+    let tol = Tolerance::default();
+    let upper = Geo::HalfPlane { o: Vector::ZERO, n: Vector::POSY };
+    assert_eq!(intersect_within(upper, Geo::Point(Vector::POSY), tol).len(), 1);
+    assert!(intersect_within(upper, Geo::Point(Vector::NEGY), tol).is_empty());
+    let clipped = intersect_within(Geo::Line(Vector::NEGY, Vector::POSY), upper, tol);
+    assert_eq!(clipped, vec![Geo::Ray(Vector::ZERO, Vector::POSY)]);
+}
+
+#[test]
+fn transform_circle() {
+    let t = Transform {
+        translation: Vector { x: 1., y: -2. },
+        rotation: 0.5,
+        scale: 3.,
+    };
+    let Geo::Circle(c, r) = transform(Geo::Circle(Vector::ZERO, 2.), &t) else {
+        panic!("expected a circle")
+    };
+    assert_eq!(c, Vector { x: 1., y: -2. });
+    assert!(r.about_eq(6.));
+}
+
+#[test]
+fn ray_params() {
+    use super::consts::SQRT_2;
+
+    let diagonal = Vector { x: 1., y: 1. }.unit();
+    let (t0, t1) = ray_ray_params(Vector::ZERO, diagonal, Vector { x: 2., y: 0. }, Vector::POSY)
+        .unwrap();
+    assert!(t0.about_eq(2. * SQRT_2));
+    assert!(t1.about_eq(2.));
+    assert_eq!(ray_ray_params(Vector::ZERO, Vector::POSX, Vector::POSY, Vector::NEGX), None);
+}
+
+#[test]
+fn on_circle() {
+    let circle = Geo::Circle(Vector::ZERO, 2.);
+    assert!(contains(circle, Vector { x: 0., y: 2. }));
+    assert!(!contains(circle, Vector { x: 0., y: 2.001 }));
+}
+
+#[test]
+fn arc() {
+    use crate::math::consts::{PI, SQRT_2};
+
+    let top = Geo::Arc {
+        c: Vector::ZERO,
+        r: 1.,
+        start: 0.,
+        end: PI,
+    };
+    let vertical = Geo::Line(Vector::ZERO, Vector::POSY);
+    assert_eq!(
+        intersect(top, vertical),
+        vec![Geo::Point(Vector { x: 0., y: 1. })]
+    );
+    // The arc's nearest point to the bottom is an endpoint.
+    assert!(dist(Vector { x: 0., y: -1. }, top).about_eq(SQRT_2));
+    // Wrapping past angle zero.
+    let right = Geo::Arc {
+        c: Vector::ZERO,
+        r: 1.,
+        start: -PI / 2.,
+        end: PI / 2.,
+    };
+    assert!(contains(right, Vector { x: 1., y: 0. }));
+    assert!(!contains(right, Vector { x: -1., y: 0. }));
+}
+
+#[test]
+fn pulley_tangents() {
+    let c1 = Vector { x: 10., y: 0. };
+    let lines = common_tangents(Vector::ZERO, 1., c1, 1.);
+    assert_eq!(lines.len(), 4);
+    // Equal radii make the external tangents parallel to the centers.
+    for &(p, v) in &lines[..2] {
+        assert!(v.y.about_zero());
+        assert!(p.y.abs().about_eq(1.));
+    }
+    for &(p, v) in &lines {
+        assert!(dist(Vector::ZERO, Geo::Line(p, v)).about_eq(1.));
+        assert!(dist(c1, Geo::Line(p, v)).about_eq(1.));
+    }
+    // Overlapping circles only have external tangents.
+    assert_eq!(common_tangents(Vector::ZERO, 1., Vector::POSX, 1.).len(), 2);
+}
+
+#[test]
+fn everywhere() {
+    let circle = Geo::Circle(Vector::ZERO, 1.);
+    assert_eq!(intersect(Geo::All, circle), vec![circle]);
+    assert_eq!(intersect(Geo::Point(Vector::POSX), Geo::All), vec![Geo::Point(Vector::POSX)]);
+    assert!(contains(Geo::All, Vector { x: 3., y: -4. }));
+    assert_eq!(choose(Geo::All), Vector::ZERO);
+}
+
+#[test]
+fn geometric_eq() {
+    let ray = Geo::Ray(Vector::ZERO, Vector { x: 2., y: 0. });
+    assert!(geo_about_eq(ray, Geo::Ray(Vector::ZERO, Vector::POSX)));
+    assert!(!geo_about_eq(ray, Geo::Ray(Vector::ZERO, Vector::NEGX)));
+    assert!(!geo_about_eq(ray, Geo::Line(Vector::ZERO, Vector::POSX)));
+    let line = Geo::Line(Vector::ZERO, Vector::POSX);
+    assert!(geo_about_eq(line, Geo::Line(Vector { x: 5., y: 0. }, Vector::NEGX * 3.)));
+    let dust = Tolerance::default().0 / 10.;
+    assert!(geo_about_eq(
+        Geo::Circle(Vector::ZERO, 1.),
+        Geo::Circle(Vector { x: dust, y: 0. }, 1. + dust)
+    ));
+}
+
+#[test]
+fn half_plane_arcs() {
+    use crate::math::consts::PI;
+
+    let upper = Geo::HalfPlane { o: Vector::ZERO, n: Vector::POSY };
+    let top = intersect(Geo::Circle(Vector::ZERO, 1.), upper);
+    assert_eq!(top.len(), 1);
+    let Geo::Arc { start, end, .. } = top[0] else {
+        panic!("expected an arc")
+    };
+    assert!(start.about_eq(0.) && end.about_eq(PI));
+    // Candidates on the wrong side are gone.
+    let line = Geo::Line(Vector::ZERO, Vector::POSY);
+    assert_eq!(intersect(top[0], line), vec![Geo::Point(Vector::POSY)]);
+
+    // An arc crossing the boundary twice keeps both ends.
+    let most = Geo::Arc { c: Vector::ZERO, r: 1., start: -PI / 4., end: 5. * PI / 4. };
+    let lower = Geo::HalfPlane { o: Vector::ZERO, n: Vector::NEGY };
+    assert_eq!(intersect(most, lower).len(), 2);
+    let (right, left) = (Vector::from_angle(-PI / 8.), Vector::from_angle(9. * PI / 8.));
+    for g in intersect(most, lower) {
+        assert_ne!(contains(g, right), contains(g, left));
+    }
+}
+
+#[test]
+fn half_plane_touch() {
+    use crate::math::consts::PI;
+
+    let upper = Geo::HalfPlane { o: Vector::ZERO, n: Vector::POSY };
+    let below = Vector { x: 0., y: -1. };
+    let circle = Geo::Circle(below, 1.);
+    assert_eq!(intersect(circle, upper), vec![Geo::Point(Vector::ZERO)]);
+    // Only an arc covering the touching point keeps it.
+    let top = Geo::Arc { c: below, r: 1., start: PI / 4., end: 3. * PI / 4. };
+    assert_eq!(intersect(top, upper), vec![Geo::Point(Vector::ZERO)]);
+    let bottom = Geo::Arc { c: below, r: 1., start: -3. * PI / 4., end: -PI / 4. };
+    assert!(intersect(bottom, upper).is_empty());
+}
+
+#[test]
+fn long_line() {
+    let line = Geo::Line(Vector::ZERO, Vector { x: 2., y: 0. });
+    assert!(contains(line, Vector::POSX));
+    assert!(dist(Vector { x: 1., y: 5. }, line).about_eq(5.));
+    assert_eq!(intersect(line, Geo::Point(Vector::POSX)), vec![Geo::Point(Vector::POSX)]);
+}
+
+#[test]
+fn long_closest() {
+    let p = Vector { x: 1., y: 5. };
+    let ray = Geo::Ray(Vector::ZERO, Vector { x: 2., y: 0. });
+    assert_eq!(closest_point(ray, p), Vector::POSX);
+    assert_eq!(closest_point(ray, Vector { x: -1., y: 5. }), Vector::ZERO);
+    assert!(contains(ray, Vector::POSX));
+    let line = Geo::Line(Vector::NEGX, Vector { x: 0., y: 3. });
+    assert_eq!(closest_point(line, p), Vector { x: -1., y: 5. });
+}
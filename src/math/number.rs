@@ -0,0 +1,116 @@
+/// Numerical type.
+#[cfg(not(feature = "f32"))]
+pub type Number = f64;
+/// Numerical type.
+#[cfg(feature = "f32")]
+pub type Number = f32;
+
+/// Constants for [`Number`].
+#[cfg(not(feature = "f32"))]
+pub use core::f64::consts;
+/// Constants for [`Number`].
+#[cfg(feature = "f32")]
+pub use core::f32::consts;
+
+#[cfg(not(feature = "f32"))]
+pub(crate) const EPSILON: Number = 1e-9;
+#[cfg(feature = "f32")]
+pub(crate) const EPSILON: Number = 1e-4;
+
+/// Float functions `core` lacks, from `libm`.
+///
+/// Tests link `std`, whose inherent methods take precedence.
+#[cfg(not(any(feature = "std", test)))]
+pub(crate) trait Float {
+    fn sqrt(self) -> Self;
+    fn sin(self) -> Self;
+    fn cos(self) -> Self;
+    fn acos(self) -> Self;
+    fn atan2(self, other: Self) -> Self;
+    fn powi(self, n: i32) -> Self;
+    fn round(self) -> Self;
+    fn rem_euclid(self, rhs: Self) -> Self;
+}
+#[cfg(not(any(feature = "std", test)))]
+impl Float for Number {
+    fn sqrt(self) -> Self {
+        libm::Libm::<Number>::sqrt(self)
+    }
+    fn sin(self) -> Self {
+        libm::Libm::<Number>::sin(self)
+    }
+    fn cos(self) -> Self {
+        libm::Libm::<Number>::cos(self)
+    }
+    fn acos(self) -> Self {
+        libm::Libm::<Number>::acos(self)
+    }
+    fn atan2(self, other: Self) -> Self {
+        libm::Libm::<Number>::atan2(self, other)
+    }
+    fn powi(self, n: i32) -> Self {
+        libm::Libm::<Number>::pow(self, n as Number)
+    }
+    fn round(self) -> Self {
+        libm::Libm::<Number>::round(self)
+    }
+    fn rem_euclid(self, rhs: Self) -> Self {
+        let r = self % rhs;
+        if r < 0. { r + rhs.abs() } else { r }
+    }
+}
+
+/// Largest difference treated as equal.
+///
+/// Defaults to [`epsilon()`].
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Tolerance(pub Number);
+impl Default for Tolerance {
+    fn default() -> Self {
+        Tolerance(EPSILON)
+    }
+}
+
+/// Default tolerance of [`AboutEq`].
+pub fn epsilon() -> Number {
+    EPSILON
+}
+
+/// Equality within a [`Tolerance`].
+///
+/// ```
+/// use gsolve::math::{AboutEq, Vector};
+///
+/// let v = Vector { x: 0.1 + 0.2, y: 0. };
+/// assert!(v.about_eq(Vector { x: 0.3, y: 0. }));
+/// ```
+pub trait AboutEq: Sized {
+    /// Whether every component differs by at most `tol`.
+    fn about_eq_within(self, v: Self, tol: Tolerance) -> bool;
+    /// Whether every component is within `tol` of zero.
+    fn about_zero_within(self, tol: Tolerance) -> bool;
+    /// [`AboutEq::about_eq_within`] the default tolerance.
+    fn about_eq(self, v: Self) -> bool {
+        self.about_eq_within(v, Tolerance::default())
+    }
+    /// [`AboutEq::about_zero_within`] the default tolerance.
+    fn about_zero(self) -> bool {
+        self.about_zero_within(Tolerance::default())
+    }
+}
+
+impl AboutEq for Number {
+    fn about_eq_within(self, v: Self, tol: Tolerance) -> bool {
+        (v - self).abs() <= tol.0
+    }
+    fn about_zero_within(self, tol: Tolerance) -> bool {
+        self.abs() <= tol.0
+    }
+}
+
+#[test]
+fn public_epsilon() {
+    assert_eq!(Tolerance::default().0, epsilon());
+    assert!(1.0.about_eq(1.0 + epsilon() / 2.));
+    assert!(!1.0.about_eq(1.0 + epsilon() * 2.));
+}
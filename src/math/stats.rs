@@ -0,0 +1,33 @@
+use super::{Aabb, Number, Vector};
+
+/// Mean position, or [`Vector::ZERO`] if there are none.
+pub fn centroid(points: &[Vector]) -> Vector {
+    if points.is_empty() {
+        return Vector::ZERO;
+    }
+    points.iter().sum::<Vector>() / points.len() as Number
+}
+
+/// Smallest box containing every point, or [`None`] if there are none.
+pub fn bounds(points: &[Vector]) -> Option<Aabb> {
+    points
+        .iter()
+        .map(|&p| Aabb { min: p, max: p })
+        .reduce(Aabb::union)
+}
+
+#[test]
+fn unit_square() {
+    let square = [
+        Vector::ZERO,
+        Vector::POSX,
+        Vector { x: 1., y: 1. },
+        Vector::POSY,
+    ];
+    assert_eq!(centroid(&square), Vector { x: 0.5, y: 0.5 });
+    let b = bounds(&square).unwrap();
+    assert_eq!(b.min, Vector::ZERO);
+    assert_eq!(b.max, Vector { x: 1., y: 1. });
+    assert_eq!(centroid(&[]), Vector::ZERO);
+    assert_eq!(bounds(&[]), None);
+}
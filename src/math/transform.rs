@@ -0,0 +1,63 @@
+use super::{Number, Vector};
+
+/// Scale, then rotation about the origin, then translation.
+///
+/// Defaults to the identity.
+#[allow(missing_docs)]
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Transform {
+    pub translation: Vector,
+    pub rotation: Number,
+    pub scale: Number,
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Self {
+            translation: Vector::ZERO,
+            rotation: 0.0,
+            scale: 1.0,
+        }
+    }
+}
+
+impl Transform {
+    /// Apply `self`, then `other`.
+    pub fn compose(self, other: Transform) -> Transform {
+        Transform {
+            translation: self.translation.transform(&other),
+            rotation: self.rotation + other.rotation,
+            scale: self.scale * other.scale,
+        }
+    }
+    /// Transform every position in place.
+    pub fn apply_to_positions(&self, positions: &mut [Vector]) {
+        for p in positions {
+            *p = p.transform(self);
+        }
+    }
+    /// Transform a direction, which ignores translation and magnitude.
+    pub fn apply_dir(&self, v: Vector) -> Vector {
+        (v * self.scale.signum()).rot(self.rotation)
+    }
+}
+
+#[test]
+fn compose() {
+    use super::AboutEq;
+    use super::consts::PI;
+
+    let rotate = Transform {
+        rotation: PI / 3.,
+        scale: 2.,
+        ..Default::default()
+    };
+    let translate = Transform {
+        translation: Vector { x: 4., y: -1. },
+        ..Default::default()
+    };
+    let p = Vector { x: 1., y: 2. };
+    let mut positions = [p];
+    rotate.compose(translate).apply_to_positions(&mut positions);
+    assert!(positions[0].about_eq(p.transform(&rotate).transform(&translate)));
+}
@@ -1,18 +1,21 @@
-use std::{
-    fmt::Display, hash::Hash, ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign}
+use core::{
+    fmt::Display, hash::Hash, iter::Sum, ops::{Add, AddAssign, Div, DivAssign, Index, IndexMut, Mul, MulAssign, Neg, Sub, SubAssign}
 };
 
-use super::{AboutEq, Number};
+#[cfg(not(any(feature = "std", test)))]
+use super::Float;
+use super::{AboutEq, Number, Tolerance, Transform};
 
 /// 2D Vector.
 #[allow(missing_docs)]
 #[derive(Debug, Default, PartialEq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Vector {
     pub x: Number,
     pub y: Number,
 }
 impl Hash for Vector {
-    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
         self.x.to_be_bytes().hash(state);
         self.y.to_be_bytes().hash(state);
     }
@@ -37,22 +40,58 @@ impl Vector {
     };
     /// Magnitude.
     pub fn mag(self) -> Number {
-        Number::sqrt(self.x * self.x + self.y * self.y)
+        Number::sqrt(self.mag_squared())
+    }
+    /// Squared magnitude.
+    pub fn mag_squared(self) -> Number {
+        self.x * self.x + self.y * self.y
     }
     /// Distance.
     pub fn dist(self, rhs: Self) -> Number {
         (rhs - self).mag()
     }
+    /// Squared distance.
+    pub fn dist_squared(self, rhs: Self) -> Number {
+        (rhs - self).mag_squared()
+    }
+    /// Linear interpolation.
+    pub fn lerp(self, rhs: Self, t: Number) -> Vector {
+        self + (rhs - self) * t
+    }
+    /// Halfway point.
+    pub fn midpoint(self, rhs: Self) -> Vector {
+        self.lerp(rhs, 0.5)
+    }
     /// Normalized
+    ///
+    /// Near-zero vectors have no direction and give [`Vector::ZERO`].
     pub fn unit(self) -> Vector {
-        let d = self.mag();
-        self / d
+        self.unit_mag().0
     }
     /// Normalized and return magnitude.
+    ///
+    /// Near-zero vectors give [`Vector::ZERO`].
     pub fn unit_mag(self) -> (Vector, Number) {
         let d = self.mag();
+        if d.about_zero() {
+            return (Vector::ZERO, d);
+        }
         (self / d, d)
     }
+    /// Shortened to at most `max` long.
+    pub fn clamp_mag(self, max: Number) -> Vector {
+        self.clamp_mag_min_max(0.0, max)
+    }
+    /// Scaled to between `min` and `max` long.
+    ///
+    /// The zero vector has no direction and stays zero.
+    pub fn clamp_mag_min_max(self, min: Number, max: Number) -> Vector {
+        let mag = self.mag();
+        if mag == 0.0 || (min <= mag && mag <= max) {
+            return self;
+        }
+        self / mag * if mag > max { max } else { min }
+    }
     /// Dot product.
     pub fn dot(self, rhs: Self) -> Number {
         self.x * rhs.x + self.y * rhs.y
@@ -61,13 +100,20 @@ impl Vector {
     pub fn cross(self, rhs: Self) -> Number {
         self.x * rhs.y - self.y * rhs.x
     }
-    /// Perpendicular with positive rotation.
+    /// Perpendicular with positive rotation, counterclockwise.
     pub fn perp(self) -> Vector {
         Vector {
             x: -self.y,
             y: self.x,
         }
     }
+    /// Perpendicular with negative rotation, clockwise.
+    pub fn perp_cw(self) -> Vector {
+        Vector {
+            x: self.y,
+            y: -self.x,
+        }
+    }
     /// Rotate.
     pub fn rot(self, angle: Number) -> Vector {
         let v = Vector::from_angle(angle);
@@ -76,6 +122,38 @@ impl Vector {
             y: self.x * v.y + self.y * v.x,
         }
     }
+    /// Component along `onto`.
+    pub fn project_onto(self, onto: Vector) -> Vector {
+        let mag_squared = onto.mag_squared();
+        if mag_squared.about_zero() {
+            return Vector::ZERO;
+        }
+        onto * (self.dot(onto) / mag_squared)
+    }
+    /// Component perpendicular to `onto`.
+    pub fn reject_from(self, onto: Vector) -> Vector {
+        self - self.project_onto(onto)
+    }
+    /// Rotate about `pivot`.
+    pub fn rotate_around(self, pivot: Vector, angle: Number) -> Vector {
+        (self - pivot).rot(angle) + pivot
+    }
+    /// Apply `t`.
+    pub fn transform(self, t: &Transform) -> Vector {
+        (self * t.scale).rot(t.rotation) + t.translation
+    }
+    /// Reflect across the line through the origin along `axis`.
+    pub fn reflect(self, axis: Vector) -> Vector {
+        if axis.about_zero() {
+            return self;
+        }
+        let n = axis.unit();
+        n * (2.0 * self.dot(n)) - self
+    }
+    /// Reflect through `pivot`.
+    pub fn reflect_about(self, pivot: Vector) -> Vector {
+        pivot * 2.0 - self
+    }
     /// Polar of length 1 to cartesian.
     pub fn from_angle(angle: Number) -> Vector {
         Vector {
@@ -83,10 +161,26 @@ impl Vector {
             y: Number::sin(angle),
         }
     }
+    /// At distance `r` from the origin in the direction `theta`.
+    pub fn from_polar(r: Number, theta: Number) -> Vector {
+        Vector::from_angle(theta) * r
+    }
+    /// Magnitude and angle, the inverse of [`Vector::from_polar`].
+    pub fn to_polar(self) -> (Number, Number) {
+        (self.mag(), self.angle())
+    }
     /// Convert to polar.
     pub fn angle(self) -> Number {
         self.y.atan2(self.x)
     }
+    /// Unsigned angle to `rhs` in `[0, PI]`.
+    pub fn angle_between(self, rhs: Self) -> Number {
+        self.signed_angle_to(rhs).abs()
+    }
+    /// Signed angle to `rhs` in `(-PI, PI]`.
+    pub fn signed_angle_to(self, rhs: Self) -> Number {
+        self.cross(rhs).atan2(self.dot(rhs))
+    }
     /// Component-wise absolute value.
     pub fn abs(self) -> Self {
         Self {
@@ -94,27 +188,106 @@ impl Vector {
             y: self.y.abs()
         }
     }
+    /// Whether neither component is infinite or NaN.
+    pub fn is_finite(self) -> bool {
+        self.x.is_finite() && self.y.is_finite()
+    }
+    /// Round each component to the nearest multiple of `grid`.
+    ///
+    /// A `grid` of zero leaves `self` unchanged.
+    pub fn snap(self, grid: Number) -> Self {
+        if grid == 0. {
+            return self;
+        }
+        Self {
+            x: (self.x / grid).round() * grid,
+            y: (self.y / grid).round() * grid,
+        }
+    }
+    /// Round each component to `decimals` decimal places.
+    pub fn round_to(self, decimals: u32) -> Self {
+        self.snap((10 as Number).powi(-(decimals as i32)))
+    }
+}
+
+impl From<Vector> for (Number, Number) {
+    fn from(v: Vector) -> Self {
+        (v.x, v.y)
+    }
+}
+
+impl From<(Number, Number)> for Vector {
+    fn from((x, y): (Number, Number)) -> Self {
+        Vector { x, y }
+    }
+}
+
+impl From<Vector> for [Number; 2] {
+    fn from(v: Vector) -> Self {
+        [v.x, v.y]
+    }
 }
 
-impl Into<(Number, Number)> for Vector {
-    fn into(self) -> (Number, Number) {
-        (self.x, self.y)
+impl From<[Number; 2]> for Vector {
+    fn from([x, y]: [Number; 2]) -> Self {
+        Vector { x, y }
+    }
+}
+#[cfg(feature = "glam")]
+impl From<glam::Vec2> for Vector {
+    fn from(v: glam::Vec2) -> Self {
+        Vector {
+            x: v.x as Number,
+            y: v.y as Number,
+        }
+    }
+}
+#[cfg(feature = "glam")]
+impl From<Vector> for glam::Vec2 {
+    // Number is already f32 with the `f32` feature.
+    #[allow(clippy::unnecessary_cast)]
+    fn from(v: Vector) -> Self {
+        glam::Vec2::new(v.x as f32, v.y as f32)
+    }
+}
+#[cfg(feature = "nalgebra")]
+impl From<nalgebra::Vector2<Number>> for Vector {
+    fn from(v: nalgebra::Vector2<Number>) -> Self {
+        Vector { x: v.x, y: v.y }
+    }
+}
+#[cfg(feature = "nalgebra")]
+impl From<Vector> for nalgebra::Vector2<Number> {
+    fn from(v: Vector) -> Self {
+        nalgebra::Vector2::new(v.x, v.y)
+    }
+}
+#[cfg(feature = "nalgebra")]
+impl From<nalgebra::Point2<Number>> for Vector {
+    fn from(p: nalgebra::Point2<Number>) -> Self {
+        Vector { x: p.x, y: p.y }
+    }
+}
+#[cfg(feature = "nalgebra")]
+impl From<Vector> for nalgebra::Point2<Number> {
+    fn from(v: Vector) -> Self {
+        nalgebra::Point2::new(v.x, v.y)
     }
 }
 
 impl Display for Vector {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(f, "({:.2}, {:.2})", self.x, self.y)
     }
 }
 
 impl AboutEq for Vector {
-    fn about_eq(self, v: Self) -> bool {
-        self.x.about_eq(v.x) && self.y.about_eq(v.y)
+    fn about_eq_within(self, v: Self, tol: Tolerance) -> bool {
+        self.x.about_eq_within(v.x, tol) && self.y.about_eq_within(v.y, tol)
     }
 
-    fn about_zero(self) -> bool {
-        self.x.about_zero() && self.y.about_zero()
+    fn about_zero_within(self, tol: Tolerance) -> bool {
+        self.x.about_zero_within(tol) && self.y.about_zero_within(tol)
     }
 }
 
@@ -154,6 +327,14 @@ impl AddAssign<Number> for Vector {
     }
 }
 
+impl Add<Vector> for Number {
+    type Output = Vector;
+
+    fn add(self, rhs: Vector) -> Self::Output {
+        rhs + self
+    }
+}
+
 impl Sub for Vector {
     type Output = Vector;
 
@@ -190,6 +371,17 @@ impl SubAssign<Number> for Vector {
     }
 }
 
+impl Sub<Vector> for Number {
+    type Output = Vector;
+
+    fn sub(self, rhs: Vector) -> Self::Output {
+        Vector {
+            x: self - rhs.x,
+            y: self - rhs.y,
+        }
+    }
+}
+
 impl Mul for Vector {
     type Output = Vector;
 
@@ -226,6 +418,14 @@ impl MulAssign<Number> for Vector {
     }
 }
 
+impl Mul<Vector> for Number {
+    type Output = Vector;
+
+    fn mul(self, rhs: Vector) -> Self::Output {
+        rhs * self
+    }
+}
+
 impl Div for Vector {
     type Output = Vector;
 
@@ -271,4 +471,180 @@ impl Neg for Vector {
             y: -self.y,
         }
     }
-}
\ No newline at end of file
+}
+impl Sum for Vector {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Vector::ZERO, Add::add)
+    }
+}
+
+impl<'a> Sum<&'a Vector> for Vector {
+    fn sum<I: Iterator<Item = &'a Vector>>(iter: I) -> Self {
+        iter.copied().sum()
+    }
+}
+
+impl Index<usize> for Vector {
+    type Output = Number;
+
+    fn index(&self, index: usize) -> &Self::Output {
+        match index {
+            0 => &self.x,
+            1 => &self.y,
+            _ => panic!("index out of bounds: a Vector has 2 components but the index is {index}"),
+        }
+    }
+}
+impl IndexMut<usize> for Vector {
+    fn index_mut(&mut self, index: usize) -> &mut Self::Output {
+        match index {
+            0 => &mut self.x,
+            1 => &mut self.y,
+            _ => panic!("index out of bounds: a Vector has 2 components but the index is {index}"),
+        }
+    }
+}
+
+#[test]
+fn lerp() {
+    let a = Vector { x: 1., y: 2. };
+    let b = Vector { x: -3., y: 5. };
+    assert_eq!(a.lerp(b, 0.0), a);
+    assert_eq!(a.lerp(b, 1.0), b);
+    assert_eq!(a.midpoint(b), a.lerp(b, 0.5));
+}
+
+#[test]
+fn reflect() {
+    assert!(Vector::POSY.reflect(Vector::POSX).about_eq(Vector::NEGY));
+    assert_eq!(Vector::POSY.reflect(Vector::ZERO), Vector::POSY);
+    let pivot = Vector { x: 1., y: 1. };
+    assert_eq!(Vector::ZERO.reflect_about(pivot), Vector { x: 2., y: 2. });
+}
+
+#[test]
+fn sum() {
+    let vs = [Vector::POSX, Vector::POSY, Vector::NEGX];
+    assert_eq!(vs.iter().sum::<Vector>(), Vector::POSY);
+    assert_eq!(vs.into_iter().sum::<Vector>(), Vector::POSY);
+}
+
+#[test]
+fn conversions() {
+    let v = Vector { x: 1., y: -2. };
+    let t: (Number, Number) = v.into();
+    assert_eq!(t, (1., -2.));
+    assert_eq!(Vector::from(t), v);
+    let a: [Number; 2] = v.into();
+    assert_eq!(a, [1., -2.]);
+    assert_eq!(Vector::from(a), v);
+}
+
+#[test]
+fn angle_between() {
+    use crate::math::consts::PI;
+
+    assert!(Vector::POSX.angle_between(Vector::POSY).about_eq(PI / 2.));
+    assert!(Vector::POSX.signed_angle_to(Vector::NEGY).about_eq(-PI / 2.));
+    assert!(Vector::POSX.angle_between(Vector::POSX * 3.).about_zero());
+    assert!(Vector::POSX.angle_between(Vector::NEGX).about_eq(PI));
+    assert!(Vector::POSX.signed_angle_to(Vector::NEGX).about_eq(PI));
+}
+
+#[test]
+fn scalar_lhs() {
+    assert_eq!(2.0 * Vector::POSX, Vector::POSX * 2.0);
+    assert_eq!(2.0 + Vector::POSX, Vector::POSX + 2.0);
+    assert_eq!(2.0 - Vector::POSX, -(Vector::POSX - 2.0));
+}
+
+#[test]
+fn rotate_around() {
+    use crate::math::consts::PI;
+
+    let pivot = Vector { x: 1., y: 1. };
+    let ccw = Vector::POSX.rotate_around(pivot, PI / 2.);
+    assert!(ccw.about_eq(Vector { x: 2., y: 1. }));
+    let cw = Vector::POSX.rotate_around(pivot, -PI / 2.);
+    assert!(cw.about_eq(Vector { x: 0., y: 1. }));
+}
+
+#[test]
+fn project() {
+    let v = Vector { x: 1., y: 1. };
+    assert_eq!(v.project_onto(Vector::POSX), Vector::POSX);
+    assert_eq!(v.reject_from(Vector::POSX), Vector::POSY);
+    assert_eq!(v.project_onto(Vector::ZERO), Vector::ZERO);
+}
+
+#[test]
+fn clamp_mag() {
+    let long = Vector { x: 3., y: 4. };
+    assert!(long.clamp_mag(2.).mag().about_eq(2.));
+    assert_eq!(long.clamp_mag(10.), long);
+    assert_eq!(Vector::ZERO.clamp_mag(1.), Vector::ZERO);
+    assert!(long.clamp_mag_min_max(6., 10.).mag().about_eq(6.));
+    let tiny = Vector { x: 1e-10, y: 0. };
+    assert_eq!(tiny.clamp_mag(1.), tiny);
+    assert!(tiny.clamp_mag_min_max(1., 2.).about_eq(Vector::POSX));
+}
+
+#[test]
+fn unit_zero() {
+    assert_eq!(Vector::ZERO.unit(), Vector::ZERO);
+    assert_eq!(Vector { x: 0., y: -3. }.unit(), Vector::NEGY);
+}
+
+#[cfg(feature = "glam")]
+#[test]
+fn glam_round_trip() {
+    let v = Vector { x: 1.5, y: -2.25 };
+    let g: glam::Vec2 = v.into();
+    assert_eq!(g, glam::Vec2::new(1.5, -2.25));
+    assert_eq!(Vector::from(g), v);
+}
+
+#[cfg(feature = "nalgebra")]
+#[test]
+fn nalgebra_round_trip() {
+    let v = Vector { x: 1.5, y: -2.25 };
+    let n: nalgebra::Vector2<Number> = v.into();
+    assert_eq!(n, nalgebra::Vector2::new(1.5, -2.25));
+    assert_eq!(Vector::from(n), v);
+    let p: nalgebra::Point2<Number> = v.into();
+    assert_eq!(p, nalgebra::Point2::new(1.5, -2.25));
+    assert_eq!(Vector::from(p), v);
+}
+
+#[test]
+fn snap() {
+    let v = Vector { x: 1.2, y: 3.7 };
+    assert!(v.snap(0.5).about_eq(Vector { x: 1.0, y: 3.5 }));
+    assert_eq!(v.snap(0.), v);
+    assert!(Vector { x: 1.234, y: -5.678 }.round_to(2).about_eq(Vector { x: 1.23, y: -5.68 }));
+}
+
+#[test]
+fn perp() {
+    assert_eq!(Vector::POSX.perp(), Vector::POSY);
+    assert_eq!(Vector::POSX.perp_cw(), Vector::NEGY);
+}
+
+#[test]
+fn index() {
+    let mut v = Vector { x: 1., y: 2. };
+    assert_eq!((v[0], v[1]), (1., 2.));
+    for i in 0..2 {
+        v[i] *= 3.;
+    }
+    assert_eq!(v, Vector { x: 3., y: 6. });
+}
+
+#[test]
+fn polar() {
+    use crate::math::consts::PI;
+
+    let (r, theta) = Vector::from_polar(5.0, PI / 6.0).to_polar();
+    assert!(r.about_eq(5.0));
+    assert!(theta.about_eq(PI / 6.0));
+}
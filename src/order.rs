@@ -1,20 +1,189 @@
+use std::{
+    collections::HashMap,
+    error::Error,
+    fmt::Display,
+    time::{Duration, Instant},
+};
+
+use itertools::Itertools;
+
+// Re-exported so paths from before these moved to the crate root still work.
+pub use crate::{PID, QID};
+
 use crate::math::{
-    Vector,
-    geo::{Geo, choose, meet},
+    AboutEq, Number, Tolerance, Vector,
+    consts::PI,
+    geo::{Geo, choose, closest_point, meet},
 };
 
-/// Internal point IDs.
+/// Reasons [`Order::solve`] can fail.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum SolveError {
+    /// Every candidate position failed further along.
+    ///
+    /// `point` is the deepest point the search reached without placing it,
+    /// a good proxy for the first unsatisfiable point.
+    StuckAt { point: PID },
+    /// A point's quantities have no common position.
+    Contradiction { point: PID },
+    /// A point's quantities produced an infinite or NaN position.
+    NonFinite { point: PID },
+    /// The search ran out of nodes or time.
+    BudgetExceeded,
+}
+impl Display for SolveError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SolveError::StuckAt { point } => {
+                write!(f, "no consistent solution; stuck at point {point}")
+            }
+            SolveError::Contradiction { point } => {
+                write!(f, "point {point} has contradictory quantities")
+            }
+            SolveError::NonFinite { point } => {
+                write!(f, "point {point} has a non-finite position")
+            }
+            SolveError::BudgetExceeded => write!(f, "solve budget exceeded"),
+        }
+    }
+}
+impl Error for SolveError {}
+
+/// Reasons [`Order::try_add_point`] can reject quantities.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum ConstraintError {
+    /// A quantity references a point not yet placed before the new one.
+    UnknownPoint(PID),
+}
+impl Display for ConstraintError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConstraintError::UnknownPoint(pid) => write!(f, "unknown point {pid}"),
+        }
+    }
+}
+impl Error for ConstraintError {}
+
+/// Limits on how long a solve may search.
+///
+/// Defaults to ten million candidate positions, one minute from the start
+/// of each solve, and the default [`Tolerance`]. Both limits are generous
+/// enough that only a runaway search should reach them.
+#[derive(Debug, Clone, Copy)]
+pub struct SolveOptions {
+    /// Most candidate positions to try, or [`usize::MAX`] for no limit.
+    pub max_nodes: usize,
+    /// How long after it starts to give up on a solve, or [`None`] for no
+    /// time limit.
+    pub time_limit: Option<Duration>,
+    /// How close intersecting geometry must be to meet.
+    pub tolerance: Tolerance,
+}
+impl Default for SolveOptions {
+    fn default() -> Self {
+        Self {
+            max_nodes: 10_000_000,
+            time_limit: Some(Duration::from_secs(60)),
+            tolerance: Tolerance::default(),
+        }
+    }
+}
+
+/// What's left of a solve's [`SolveOptions`] limits.
+#[derive(Clone, Copy)]
+struct Budget {
+    nodes: usize,
+    max_nodes: usize,
+    deadline: Option<Instant>,
+}
+impl Budget {
+    /// Nodes between checks of the clock, which is slow to read.
+    const CLOCK_INTERVAL: usize = 1024;
+
+    /// Start the clock on `options`.
+    fn new(options: &SolveOptions) -> Self {
+        Budget {
+            nodes: 0,
+            max_nodes: options.max_nodes,
+            deadline: options.time_limit.map(|limit| Instant::now() + limit),
+        }
+    }
+    /// Count one more node against the limits.
+    fn spend(&mut self) -> Result<(), SolveError> {
+        self.nodes += 1;
+        let check_clock = self.nodes.is_multiple_of(Self::CLOCK_INTERVAL);
+        let late = || self.deadline.is_some_and(|d| Instant::now() >= d);
+        if self.nodes > self.max_nodes || (check_clock && late()) {
+            return Err(SolveError::BudgetExceeded);
+        }
+        Ok(())
+    }
+}
+
+/// Geometry from the positions of a quantity's points.
 ///
-/// Currently [`usize`].
-pub type PID = usize;
+/// Shared across threads by [`Order::solve_parallel`], so it must be
+/// `Send + Sync` with the `rayon` feature.
+#[cfg(feature = "rayon")]
+pub type QuantityFn = Box<dyn Fn(Vec<Vector>) -> Vec<Geo> + Send + Sync>;
+/// Geometry from the positions of a quantity's points.
+#[cfg(not(feature = "rayon"))]
+pub type QuantityFn = Box<dyn Fn(Vec<Vector>) -> Vec<Geo>>;
 
 pub struct Quantity {
-    pub func: Box<dyn Fn(Vec<Vector>) -> Vec<Geo>>,
+    pub func: QuantityFn,
     pub points: Vec<PID>,
+    /// What the quantity measures, e.g. `distance 10 from P0`.
+    pub label: String,
+    /// Points the placed point is measured from, such as the center of a
+    /// distance. Points only used for a direction or side are left out.
+    pub measured: Vec<PID>,
+}
+impl Quantity {
+    /// A quantity measured from all of `points`, with a generic label.
+    pub fn new(func: QuantityFn, points: Vec<PID>) -> Self {
+        let label = if points.is_empty() {
+            "quantity".to_string()
+        } else {
+            format!("quantity on {}", points.iter().map(|p| format!("P{p}")).join(", "))
+        };
+        Quantity {
+            func,
+            measured: points.clone(),
+            points,
+            label,
+        }
+    }
+    /// Replace the label.
+    pub fn with_label(mut self, label: impl Into<String>) -> Self {
+        self.label = label.into();
+        self
+    }
+    /// Replace the points the quantity is measured from.
+    pub fn with_measured(mut self, measured: Vec<PID>) -> Self {
+        self.measured = measured;
+        self
+    }
+    fn geo(&self, positions: &[Vector]) -> Vec<Geo> {
+        (self.func)(self.points.iter().map(|p| positions[*p]).collect())
+    }
+    /// Closest position to `p` which satisfies this quantity.
+    fn nearest(&self, positions: &[Vector], p: Vector) -> Option<Vector> {
+        self.geo(positions)
+            .into_iter()
+            .map(|g| closest_point(g, p))
+            .min_by(|a, b| p.dist_squared(*a).total_cmp(&p.dist_squared(*b)))
+    }
+    /// Distance from `p` to the nearest position satisfying this quantity.
+    ///
+    /// Infinite if no position does.
+    pub fn residual(&self, positions: &[Vector], p: Vector) -> Number {
+        self.nearest(positions, p).map_or(Number::INFINITY, |c| p.dist(c))
+    }
 }
 #[derive(Default)]
 pub struct Order {
-    order: Vec<Vec<Quantity>>,
+    pub(crate) order: Vec<Vec<Quantity>>,
 }
 impl Order {
     pub fn add_point(&mut self, quantities: Vec<Quantity>) -> PID {
@@ -22,49 +191,870 @@ impl Order {
         self.order.push(quantities);
         pid
     }
-    fn solve_iter(&self, i: usize, positions: &mut Vec<Vector>) -> Result<(), &'static str> {
-        let Some(quantities) = self.order.get(i) else {
-            return Ok(());
-        };
-        let result = quantities
+    /// [`Order::add_point`], but checks every referenced point comes
+    /// before the new one.
+    ///
+    /// Leaves the order unmodified on error.
+    pub fn try_add_point(&mut self, quantities: Vec<Quantity>) -> Result<PID, ConstraintError> {
+        let pid = self.order.len();
+        if let Some(&unknown) = quantities
             .iter()
-            .map(|q| (q.func)(q.points.iter().map(|p| positions[*p]).collect()))
-            .reduce(meet)
-            .ok_or("Empty Quantities")?;
-        for position in result {
-            positions[i] = choose(position);
-            if self.solve_iter(i + 1, positions).is_ok() {
+            .flat_map(|q| &q.points)
+            .find(|p| **p >= pid)
+        {
+            return Err(ConstraintError::UnknownPoint(unknown));
+        }
+        Ok(self.add_point(quantities))
+    }
+    /// Geometry point `i` may lie on, given the earlier points' positions.
+    pub fn possibility_space(&self, positions: &[Vector], i: PID) -> Vec<Geo> {
+        self.candidates(i, positions, Tolerance::default())
+    }
+    fn candidates(&self, i: usize, positions: &[Vector], tol: Tolerance) -> Vec<Geo> {
+        if self.order[i].is_empty() {
+            // An unconstrained point anchors its component where it already is.
+            return vec![Geo::Point(positions[i])];
+        }
+        let is_region = |geos: &Vec<Geo>| {
+            !geos.is_empty() && geos.iter().all(|g| matches!(g, Geo::HalfPlane { .. }))
+        };
+        let (regions, others): (Vec<Vec<Geo>>, Vec<Vec<Geo>>) =
+            self.order[i].iter().map(|q| q.geo(positions)).partition(is_region);
+        // Regions only filter what the other quantities leave. With nothing
+        // else to place it, the point stays where it is if the regions allow.
+        let placed = others
+            .into_iter()
+            .reduce(|g0, g1| meet(g0, g1, tol))
+            .unwrap_or_else(|| vec![Geo::Point(positions[i])]);
+        regions.into_iter().fold(placed, |g0, region| meet(g0, region, tol))
+    }
+    /// Groups of points linked by their quantities, each in order.
+    ///
+    /// Moving one group never helps another, so each is searched on its
+    /// own.
+    fn components(&self) -> Vec<Vec<PID>> {
+        // Union-find, with each group rooted at its first point.
+        fn find(root: &mut [PID], mut p: PID) -> PID {
+            while root[p] != p {
+                root[p] = root[root[p]];
+                p = root[p];
+            }
+            p
+        }
+        let mut root: Vec<PID> = (0..self.order.len()).collect();
+        for (i, quantities) in self.order.iter().enumerate() {
+            for &p in quantities.iter().flat_map(|q| &q.points) {
+                let (a, b) = (find(&mut root, i), find(&mut root, p));
+                root[a.max(b)] = a.min(b);
+            }
+        }
+        let mut groups: Vec<Vec<PID>> = Vec::new();
+        let mut group = vec![0; self.order.len()];
+        for i in 0..self.order.len() {
+            let r = find(&mut root, i);
+            if r == i {
+                group[i] = groups.len();
+                groups.push(vec![i]);
+            } else {
+                group[i] = group[r];
+                groups[group[r]].push(i);
+            }
+        }
+        groups
+    }
+    /// Place every point from `first` on at its first solution.
+    ///
+    /// With a `guess`, candidates nearest the guessed position are tried
+    /// first. Points before `first` are already placed.
+    fn search(
+        &self,
+        first: PID,
+        positions: &mut [Vector],
+        guess: Option<&[Vector]>,
+        options: &SolveOptions,
+    ) -> Result<(), SolveError> {
+        let mut budget = Budget::new(options);
+        for component in self.components() {
+            let points: Vec<PID> = component.into_iter().filter(|&p| p >= first).collect();
+            self.search_component(&points, positions, guess, options, &mut budget, |_| true)?;
+        }
+        Ok(())
+    }
+    /// Depth-first search over candidate positions for `points`.
+    ///
+    /// Calls `found` each time all of `points` are placed, until it
+    /// returns `true`. `budget` counts positions tried against the limits.
+    fn search_component(
+        &self,
+        points: &[PID],
+        positions: &mut [Vector],
+        guess: Option<&[Vector]>,
+        options: &SolveOptions,
+        budget: &mut Budget,
+        mut found: impl FnMut(&[Vector]) -> bool,
+    ) -> Result<(), SolveError> {
+        let mut search = Search::new(self, points);
+        loop {
+            if let Step::Found = search.step(self, positions, guess, options, budget)?
+                && found(positions)
+            {
                 return Ok(());
             }
         }
-        Err("Unsolved")
     }
-    pub fn solve(self) -> Result<Vec<Vector>, &'static str> {
+    /// Solve within the default [`SolveOptions`].
+    pub fn solve(&self) -> Result<Vec<Vector>, SolveError> {
+        self.solve_with(SolveOptions::default())
+    }
+    /// Solve within the limits of `options`.
+    pub fn solve_with(&self, options: SolveOptions) -> Result<Vec<Vector>, SolveError> {
         let mut positions = vec![Vector::ZERO; self.order.len()];
-        self.solve_iter(1, &mut positions)?;
+        self.search(0, &mut positions, None, &options)?;
+        Ok(positions)
+    }
+    /// Solve starting from a previous solution.
+    ///
+    /// Unconstrained points are placed at their initial positions and every
+    /// other point at the candidate nearest its initial position.
+    pub fn solve_from(&self, initial: Vec<Vector>) -> Result<Vec<Vector>, SolveError> {
+        let mut guess = initial;
+        guess.resize(self.order.len(), Vector::ZERO);
+        let mut positions = guess.clone();
+        self.search(0, &mut positions, Some(&guess), &SolveOptions::default())?;
         Ok(positions)
     }
+    /// The solution with the least total displacement from `initial`.
+    ///
+    /// Like [`Order::solve_from`], but searches every solution instead of
+    /// stopping at the first, so it may be much slower. Fails rather than
+    /// settle for a solution that might not be nearest if the search runs
+    /// out of budget.
+    pub fn solve_nearest(&self, initial: &[Vector]) -> Result<Vec<Vector>, SolveError> {
+        let mut guess = initial.to_vec();
+        guess.resize(self.order.len(), Vector::ZERO);
+        let options = SolveOptions::default();
+        let mut budget = Budget::new(&options);
+        let mut positions = guess.clone();
+        // Components move independently, so each can be nearest on its own.
+        for component in self.components() {
+            let displacement = |p: &[Vector]| -> Number {
+                component.iter().map(|&i| p[i].dist(guess[i])).sum()
+            };
+            let mut best: Option<(Number, Vec<Vector>)> = None;
+            let result = self.search_component(
+                &component,
+                &mut positions,
+                Some(&guess),
+                &options,
+                &mut budget,
+                |p| {
+                    let d = displacement(p);
+                    if best.as_ref().is_none_or(|(best_d, _)| d < *best_d) {
+                        best = Some((d, p.to_vec()));
+                    }
+                    false
+                },
+            );
+            match (best, result) {
+                (_, Err(e @ (SolveError::BudgetExceeded | SolveError::NonFinite { .. }))) => {
+                    return Err(e);
+                }
+                (Some((_, solution)), _) => {
+                    for &i in &component {
+                        positions[i] = solution[i];
+                    }
+                }
+                (None, Err(e)) => return Err(e),
+                (None, Ok(())) => {
+                    unreachable!("the search only ends early once a solution is found")
+                }
+            }
+        }
+        Ok(positions)
+    }
+    /// Sum of squared distances from each point to its quantities.
+    pub fn residual(&self, positions: &[Vector]) -> Number {
+        self.order
+            .iter()
+            .enumerate()
+            .flat_map(|(i, quantities)| {
+                let p = positions[i];
+                quantities
+                    .iter()
+                    .filter_map(move |q| q.nearest(positions, p))
+                    .map(move |c| p.dist_squared(c))
+            })
+            .sum()
+    }
+    /// One line per point listing the quantities that place it, e.g.
+    /// `P3: distance 5 from P0 ∩ distance 10 from P2`.
+    pub fn describe_order(&self) -> Vec<String> {
+        self.order
+            .iter()
+            .enumerate()
+            .map(|(i, quantities)| {
+                if quantities.is_empty() {
+                    return format!("P{i}: anchor");
+                }
+                format!("P{i}: {}", quantities.iter().map(|q| &q.label).join(" ∩ "))
+            })
+            .collect()
+    }
+    /// Each quantity's residual, for use with external optimizers.
+    ///
+    /// Ordered by point, then by index among that point's quantities, the
+    /// same as [`QID`]s. A quantity with no satisfying position has an
+    /// infinite residual.
+    pub fn residual_vector(&self, positions: &[Vector]) -> Vec<Number> {
+        self.order
+            .iter()
+            .enumerate()
+            .flat_map(|(i, quantities)| {
+                quantities.iter().map(move |q| q.residual(positions, positions[i]))
+            })
+            .collect()
+    }
+    /// Length of [`Order::residual_vector`].
+    pub fn residual_dim(&self) -> usize {
+        self.order.iter().map(Vec::len).sum()
+    }
+    /// Check every quantity holds within `tol`.
+    ///
+    /// Otherwise lists each violated quantity with its residual.
+    pub fn verify(&self, positions: &[Vector], tol: Tolerance) -> Result<(), Vec<(QID, Number)>> {
+        let violations: Vec<(QID, Number)> = self
+            .order
+            .iter()
+            .enumerate()
+            .flat_map(|(i, quantities)| {
+                quantities
+                    .iter()
+                    .enumerate()
+                    .map(move |(j, q)| ((i, j), q.residual(positions, positions[i])))
+            })
+            .filter(|&(_, r)| !r.about_zero_within(tol))
+            .collect();
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(violations)
+        }
+    }
+    /// Tighten a solution by repeatedly moving each point toward its
+    /// quantities.
+    ///
+    /// Unconstrained points are anchors and stay fixed.
+    pub fn refine(&self, positions: &mut [Vector], iters: usize) {
+        self.refine_weighted(positions, &HashMap::new(), iters);
+    }
+    /// [`Order::refine`], moving further toward quantities with more
+    /// weight.
+    ///
+    /// Quantities missing from `weights` have a weight of 1. Converges on
+    /// the weighted least-squares fit when the quantities disagree.
+    pub fn refine_weighted(
+        &self,
+        positions: &mut [Vector],
+        weights: &HashMap<QID, Number>,
+        iters: usize,
+    ) {
+        for _ in 0..iters {
+            for i in 0..self.order.len() {
+                if self.order[i].is_empty() {
+                    continue;
+                }
+                let p = positions[i];
+                let targets: Vec<(Vector, Number)> = self.order[i]
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(j, q)| {
+                        let w = weights.get(&(i, j)).copied().unwrap_or(1.);
+                        q.nearest(positions, p).map(|c| (c, w))
+                    })
+                    .collect();
+                let total: Number = targets.iter().map(|&(_, w)| w).sum();
+                if total == 0. {
+                    continue;
+                }
+                let step: Vector = targets.iter().map(|&(c, w)| (c - p) * w).sum();
+                positions[i] += step / total;
+            }
+        }
+    }
+    /// Solve exactly if possible, otherwise fit the quantities by weighted
+    /// least squares.
+    ///
+    /// The fit places each point on what its quantities have in common, or
+    /// on its first quantity where they have nothing in common, then runs
+    /// [`Order::refine_weighted`] for `iters` iterations.
+    pub fn solve_least_squares(
+        &self,
+        weights: &HashMap<QID, Number>,
+        iters: usize,
+    ) -> Vec<Vector> {
+        if let Ok(positions) = self.solve() {
+            return positions;
+        }
+        let tol = Tolerance::default();
+        let mut positions = vec![Vector::ZERO; self.order.len()];
+        for i in 0..self.order.len() {
+            let mut geos = self.candidates(i, &positions, tol);
+            if geos.is_empty()
+                && let Some(q) = self.order[i].first()
+            {
+                // The quantities disagree, so start from the first alone.
+                geos = q.geo(&positions);
+            }
+            if let Some(&g) = geos.first() {
+                positions[i] = choose(g);
+            }
+        }
+        self.refine_weighted(&mut positions, weights, iters);
+        positions
+    }
+    /// [`Order::solve`], exploring the first branching point's candidates
+    /// in parallel.
+    ///
+    /// Gives the same result as [`Order::solve`], since branches are
+    /// considered in the order it would try them.
+    #[cfg(feature = "rayon")]
+    pub fn solve_parallel(&self) -> Result<Vec<Vector>, SolveError> {
+        use rayon::prelude::*;
+
+        let options = SolveOptions::default();
+        let mut budget = Budget::new(&options);
+        let mut positions = vec![Vector::ZERO; self.order.len()];
+        let mut components = self.components().into_iter();
+        // Search serially until a point has a choice of candidates.
+        let (search, i, branches) = 'serial: loop {
+            let Some(component) = components.next() else {
+                return Ok(positions);
+            };
+            let mut search = Search::new(self, &component);
+            loop {
+                match search.step(self, &mut positions, None, &options, &mut budget)? {
+                    Step::Found => break,
+                    Step::Placed(i, p) => {
+                        let mut branches = vec![p];
+                        branches.extend(search.take_alternatives());
+                        if branches.len() > 1 {
+                            break 'serial (search, i, branches);
+                        }
+                    }
+                }
+            }
+        };
+        let rest: Vec<Vec<PID>> = components.collect();
+        // Getting stuck in the branching component moves on to the next
+        // branch, like backtracking would. Any other error is final.
+        let outcomes: Vec<Result<Result<Vec<Vector>, SolveError>, PID>> = branches
+            .into_par_iter()
+            .map(|p| {
+                let mut search = search.clone();
+                let mut positions = positions.clone();
+                let mut budget = budget;
+                positions[i] = p;
+                loop {
+                    match search.step(self, &mut positions, None, &options, &mut budget) {
+                        Ok(Step::Found) => break,
+                        Ok(Step::Placed(..)) => {}
+                        Err(SolveError::StuckAt { point }) => return Err(point),
+                        Err(e) => return Ok(Err(e)),
+                    }
+                }
+                for component in &rest {
+                    let result = self.search_component(
+                        component,
+                        &mut positions,
+                        None,
+                        &options,
+                        &mut budget,
+                        |_| true,
+                    );
+                    if let Err(e) = result {
+                        return Ok(Err(e));
+                    }
+                }
+                Ok(Ok(positions))
+            })
+            .collect();
+        let mut deepest = i;
+        for outcome in outcomes {
+            match outcome {
+                Ok(result) => return result,
+                Err(point) => deepest = deepest.max(point),
+            }
+        }
+        Err(SolveError::StuckAt { point: deepest })
+    }
+    /// Every position [`Order::solve`] tries, in order.
+    ///
+    /// The search advances one position per item. A point is yielded again
+    /// each time backtracking moves it, so the last position yielded for
+    /// each point is where the solve leaves it. If the solve fails, its
+    /// error is the last item.
+    pub fn solve_steps(&self) -> impl Iterator<Item = Result<(PID, Vector), SolveError>> + '_ {
+        let mut positions = vec![Vector::ZERO; self.order.len()];
+        let options = SolveOptions::default();
+        let mut budget = Budget::new(&options);
+        let mut components = self.components().into_iter();
+        let mut search: Option<Search> = None;
+        let mut failed = false;
+        std::iter::from_fn(move || {
+            while !failed {
+                let mut current = match search.take() {
+                    Some(current) => current,
+                    None => Search::new(self, &components.next()?),
+                };
+                match current.step(self, &mut positions, None, &options, &mut budget) {
+                    Ok(Step::Placed(i, p)) => {
+                        search = Some(current);
+                        return Some(Ok((i, p)));
+                    }
+                    // On to the next component.
+                    Ok(Step::Found) => {}
+                    Err(e) => {
+                        failed = true;
+                        return Some(Err(e));
+                    }
+                }
+            }
+            None
+        })
+    }
+    /// Every solution, up to `max_solutions`.
+    ///
+    /// Solutions combine each component's solutions, the last component
+    /// varying fastest. Fails rather than return only some of the solutions
+    /// if the search runs out of budget.
+    pub fn solve_all(&self, max_solutions: usize) -> Result<Vec<Vec<Vector>>, SolveError> {
+        if max_solutions == 0 {
+            return Ok(Vec::new());
+        }
+        let mut positions = vec![Vector::ZERO; self.order.len()];
+        let options = SolveOptions::default();
+        let mut budget = Budget::new(&options);
+        let components = self.components();
+        let mut choices: Vec<Vec<Vec<Vector>>> = Vec::new();
+        for component in &components {
+            let mut solutions: Vec<Vec<Vector>> = Vec::new();
+            let result = self.search_component(
+                component,
+                &mut positions,
+                None,
+                &options,
+                &mut budget,
+                |p| {
+                    solutions.push(component.iter().map(|&i| p[i]).collect());
+                    solutions.len() >= max_solutions
+                },
+            );
+            match result {
+                Err(e @ (SolveError::BudgetExceeded | SolveError::NonFinite { .. })) => {
+                    return Err(e);
+                }
+                // Stuck once every branch is tried, so there are no more.
+                _ if solutions.is_empty() => return Ok(Vec::new()),
+                _ => {}
+            }
+            choices.push(solutions);
+        }
+        Ok(choices
+            .into_iter()
+            .multi_cartesian_product()
+            .take(max_solutions)
+            .map(|choice| {
+                for (component, solution) in components.iter().zip(choice) {
+                    for (&i, p) in component.iter().zip(solution) {
+                        positions[i] = p;
+                    }
+                }
+                positions.clone()
+            })
+            .collect())
+    }
 }
 
-#[test]
-fn rect() {
-    use std::f64::consts::PI;
-    use crate::math::Number;
+/// What a [`Search`] did in one step.
+enum Step {
+    /// Moved a point to a candidate position.
+    Placed(PID, Vector),
+    /// Every point is placed.
+    Found,
+}
 
-    fn distance(point: PID, value: Number) -> Quantity {
-        let func = move |pos: Vec<Vector>| vec![Geo::Circle(pos[0], value)];
-        Quantity {
-            func: Box::new(func),
-            points: vec![point],
+/// Depth-first search over candidate positions, one step at a time.
+#[derive(Clone)]
+struct Search {
+    /// Points to place, in order.
+    points: Vec<PID>,
+    /// Remaining candidates for each placed point in `points`.
+    /// Kept on the heap so long chains can't overflow the call stack.
+    stack: Vec<std::vec::IntoIter<Vector>>,
+    /// Deepest point the search has reached.
+    deepest: PID,
+    /// Whether the last step placed a point, so the next moves on.
+    descend: bool,
+}
+impl Search {
+    /// Search for `points`. Unconstrained points anchor the search where
+    /// they already are.
+    fn new(order: &Order, points: &[PID]) -> Self {
+        Search {
+            points: points.iter().copied().filter(|&p| !order.order[p].is_empty()).collect(),
+            stack: Vec::new(),
+            deepest: 0,
+            descend: true,
         }
     }
-    fn orientation(point: PID, value: Number) -> Quantity {
-        let func = move |pos: Vec<Vector>| vec![Geo::Ray(pos[0], Vector::from_angle(value))];
-        Quantity {
-            func: Box::new(func),
-            points: vec![point],
+    /// Place the next point, or backtrack to the next candidate.
+    ///
+    /// With a `guess`, candidates nearest the guessed position are tried
+    /// first. `budget` counts positions tried against the limits.
+    fn step(
+        &mut self,
+        order: &Order,
+        positions: &mut [Vector],
+        guess: Option<&[Vector]>,
+        options: &SolveOptions,
+        budget: &mut Budget,
+    ) -> Result<Step, SolveError> {
+        if self.descend {
+            let Some(&i) = self.points.get(self.stack.len()) else {
+                self.descend = false;
+                return Ok(Step::Found);
+            };
+            self.deepest = self.deepest.max(i);
+            let result = order.candidates(i, positions, options.tolerance);
+            if result.is_empty() && self.stack.is_empty() {
+                return Err(SolveError::Contradiction { point: i });
+            }
+            let result: Vec<Vector> = match guess {
+                Some(guess) => {
+                    let mut result: Vec<Vector> =
+                        result.into_iter().map(|g| closest_point(g, guess[i])).collect();
+                    result.sort_by(|a, b| {
+                        a.dist_squared(guess[i]).total_cmp(&b.dist_squared(guess[i]))
+                    });
+                    result
+                }
+                None => result.into_iter().map(choose).collect(),
+            };
+            if !result.iter().all(|p| p.is_finite()) {
+                return Err(SolveError::NonFinite { point: i });
+            }
+            self.stack.push(result.into_iter());
+        }
+        // Take the next candidate, backtracking when a point runs out.
+        loop {
+            let Some(remaining) = self.stack.last_mut() else {
+                return Err(SolveError::StuckAt { point: self.deepest });
+            };
+            if let Some(position) = remaining.next() {
+                budget.spend()?;
+                let i = self.points[self.stack.len() - 1];
+                positions[i] = position;
+                self.descend = true;
+                return Ok(Step::Placed(i, position));
+            }
+            self.stack.pop();
         }
     }
+    /// Take the untried candidates for the last point placed.
+    #[cfg(feature = "rayon")]
+    fn take_alternatives(&mut self) -> Vec<Vector> {
+        self.stack
+            .last_mut()
+            .map(|remaining| std::mem::take(remaining).collect())
+            .unwrap_or_default()
+    }
+}
+
+/// Solved distance between `a` and `b`, as [`distance`] measures it.
+pub fn measured_distance(positions: &[Vector], a: PID, b: PID) -> Number {
+    positions[a].dist(positions[b])
+}
+
+/// Solved direction from `a` to `b` in radians, as [`orientation`]
+/// measures it.
+pub fn measured_orientation(positions: &[Vector], a: PID, b: PID) -> Number {
+    (positions[b] - positions[a]).angle()
+}
+
+/// At `value` from `point`.
+pub fn distance(point: PID, value: Number) -> Quantity {
+    let func = move |pos: Vec<Vector>| vec![Geo::Circle(pos[0], value)];
+    Quantity::new(Box::new(func), vec![point])
+        .with_label(format!("distance {value} from P{point}"))
+}
+
+/// In the direction `value` radians from `point`.
+pub fn orientation(point: PID, value: Number) -> Quantity {
+    let func = move |pos: Vec<Vector>| vec![Geo::Ray(pos[0], Vector::from_angle(value))];
+    Quantity::new(Box::new(func), vec![point])
+        .with_label(format!("orientation {value} from P{point}"))
+}
+
+/// In the direction from `ref_a` to `ref_b`, starting at `point`.
+///
+/// Like [`orientation`] with the angle taken from a placed segment.
+pub fn orientation_parallel(point: PID, ref_a: PID, ref_b: PID) -> Quantity {
+    let func = move |pos: Vec<Vector>| vec![Geo::Ray(pos[0], (pos[2] - pos[1]).unit())];
+    Quantity::new(Box::new(func), vec![point, ref_a, ref_b])
+        .with_label(format!("orientation of P{ref_a}-P{ref_b} from P{point}"))
+        .with_measured(vec![point])
+}
+
+/// On the circle of `radius` about `center`.
+///
+/// The same space as [`distance`], named for figures built from circles.
+pub fn on_circle(center: PID, radius: Number) -> Quantity {
+    distance(center, radius).with_label(format!("on radius {radius} about P{center}"))
+}
+
+/// Exactly at `at`.
+///
+/// Depends on no other points, so it can place point 0.
+pub fn fixed(at: Vector) -> Quantity {
+    let func = move |_: Vec<Vector>| vec![Geo::Point(at)];
+    Quantity::new(Box::new(func), vec![])
+        .with_label(format!("fixed at {at}"))
+}
+
+/// In a direction `value` radians either side of the direction from
+/// `ref_origin` to `ref_other`, starting at `origin`.
+pub fn angle_from(origin: PID, ref_origin: PID, ref_other: PID, value: Number) -> Quantity {
+    let func = move |pos: Vec<Vector>| {
+        let base = (pos[2] - pos[1]).unit();
+        vec![
+            Geo::Ray(pos[0], base.rot(value)),
+            Geo::Ray(pos[0], base.rot(-value)),
+        ]
+    };
+    Quantity::new(Box::new(func), vec![origin, ref_origin, ref_other])
+        .with_label(format!("angle {value} from P{ref_origin}-P{ref_other} at P{origin}"))
+        .with_measured(vec![origin])
+}
+
+/// A vertex from which `a` and `b` are `value` apart.
+///
+/// The vertex lies on one of the two inscribed-angle circles through `a`
+/// and `b`: the major arc when `value` is acute, the minor arc when it is
+/// obtuse. The rest of each circle sees the supplementary angle, so it is
+/// left out. Nothing satisfies a `value` outside `(0, PI)`.
+pub fn inscribed_angle(a: PID, b: PID, value: Number) -> Quantity {
+    let func = move |pos: Vec<Vector>| {
+        if !(value > 0. && value < PI) {
+            return vec![];
+        }
+        let (a, b) = (pos[0], pos[1]);
+        let half = a.dist(b) / 2.;
+        let n = (b - a).unit().perp();
+        let offset = n * (half / value.tan());
+        let r = half / value.sin();
+        let m = a.midpoint(b);
+        // Each circle's arc on the `n` side of the chord, then on the other.
+        let (c0, c1) = (m + offset, m - offset);
+        vec![
+            Geo::Arc { c: c0, r, start: (b - c0).angle(), end: (a - c0).angle() },
+            Geo::Arc { c: c1, r, start: (a - c1).angle(), end: (b - c1).angle() },
+        ]
+    };
+    Quantity::new(Box::new(func), vec![a, b])
+        .with_label(format!("inscribed angle {value} over P{a}-P{b}"))
+}
+
+/// On the line through `a` and `b`.
+pub fn collinear(a: PID, b: PID) -> Quantity {
+    let func = move |pos: Vec<Vector>| vec![Geo::Line(pos[0], (pos[1] - pos[0]).unit())];
+    Quantity::new(Box::new(func), vec![a, b])
+        .with_label(format!("collinear with P{a}-P{b}"))
+}
+
+/// On the line through `known` parallel to `ref_a` to `ref_b`.
+///
+/// Any number of segments can share one reference segment.
+pub fn parallel(known: PID, ref_a: PID, ref_b: PID) -> Quantity {
+    let func = move |pos: Vec<Vector>| vec![Geo::Line(pos[0], (pos[2] - pos[1]).unit())];
+    Quantity::new(Box::new(func), vec![known, ref_a, ref_b])
+        .with_label(format!("parallel to P{ref_a}-P{ref_b} through P{known}"))
+        .with_measured(vec![known])
+}
+
+/// On the line through `known` perpendicular to `ref_a` to `ref_b`.
+pub fn perpendicular(known: PID, ref_a: PID, ref_b: PID) -> Quantity {
+    let func = move |pos: Vec<Vector>| vec![Geo::Line(pos[0], (pos[2] - pos[1]).unit().perp())];
+    Quantity::new(Box::new(func), vec![known, ref_a, ref_b])
+        .with_label(format!("perpendicular to P{ref_a}-P{ref_b} through P{known}"))
+        .with_measured(vec![known])
+}
+
+/// The mirror image of `q` across the line through `a` and `b`.
+pub fn symmetric(q: PID, a: PID, b: PID) -> Quantity {
+    let func = move |pos: Vec<Vector>| {
+        let (q, a, b) = (pos[0], pos[1], pos[2]);
+        vec![Geo::Point((q - a).reflect(b - a) + a)]
+    };
+    Quantity::new(Box::new(func), vec![q, a, b])
+        .with_label(format!("mirror of P{q} across P{a}-P{b}"))
+        .with_measured(vec![])
+}
+
+/// Which side of a directed line a point is on.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Polarity {
+    /// Counterclockwise, to the left.
+    Positive,
+    /// Clockwise, to the right.
+    Negative,
+}
+
+/// On the `polarity` side of the line from `a` through `b`.
+///
+/// The placed point `c` then has `(b - a).cross(c - b)` of that sign,
+/// which picks one of two mirrored solutions.
+pub fn chirality(a: PID, b: PID, polarity: Polarity) -> Quantity {
+    let func = move |pos: Vec<Vector>| {
+        let dir = (pos[1] - pos[0]).unit();
+        let n = match polarity {
+            Polarity::Positive => dir.perp(),
+            Polarity::Negative => dir.perp_cw(),
+        };
+        vec![Geo::HalfPlane { o: pos[1], n }]
+    };
+    let side = match polarity {
+        Polarity::Positive => "left",
+        Polarity::Negative => "right",
+    };
+    Quantity::new(Box::new(func), vec![a, b])
+        .with_label(format!("{side} of P{a}-P{b}"))
+        .with_measured(vec![])
+}
+
+/// Level with `point`.
+pub fn horizontal(point: PID) -> Quantity {
+    let func = move |pos: Vec<Vector>| vec![Geo::Line(pos[0], Vector::POSX)];
+    Quantity::new(Box::new(func), vec![point])
+        .with_label(format!("level with P{point}"))
+}
+
+/// Directly above or below `point`.
+pub fn vertical(point: PID) -> Quantity {
+    let func = move |pos: Vec<Vector>| vec![Geo::Line(pos[0], Vector::POSY)];
+    Quantity::new(Box::new(func), vec![point])
+        .with_label(format!("above or below P{point}"))
+}
+
+/// On a line through `known` tangent to the circle of `radius` about
+/// `center`.
+///
+/// Both tangent lines are candidates. Whether `known` is on the circle is
+/// judged with the default [`Tolerance`], not [`SolveOptions::tolerance`].
+pub fn tangent(center: PID, radius: Number, known: PID) -> Quantity {
+    let func = move |pos: Vec<Vector>| {
+        let (c, k) = (pos[0], pos[1]);
+        let (dir, d) = (k - c).unit_mag();
+        if d.about_eq(radius) {
+            // `known` is on the circle.
+            return vec![Geo::Line(k, dir.perp())];
+        }
+        if d < radius {
+            // `known` is inside the circle.
+            return vec![];
+        }
+        let angle = (radius / d).acos();
+        [angle, -angle]
+            .into_iter()
+            .map(|a| Geo::Line(k, (c + dir.rot(a) * radius - k).unit()))
+            .collect()
+    };
+    Quantity::new(Box::new(func), vec![center, known])
+        .with_label(format!("tangent through P{known} to radius {radius} about P{center}"))
+        .with_measured(vec![known])
+}
+
+/// Halfway between `a` and `b`.
+pub fn midpoint(a: PID, b: PID) -> Quantity {
+    let func = move |pos: Vec<Vector>| vec![Geo::Point(pos[0].midpoint(pos[1]))];
+    Quantity::new(Box::new(func), vec![a, b])
+        .with_label(format!("midpoint of P{a}-P{b}"))
+}
+
+/// The end of a segment with midpoint `mid` and other end `end`.
+pub fn midpoint_end(mid: PID, end: PID) -> Quantity {
+    let func = move |pos: Vec<Vector>| vec![Geo::Point(pos[1].reflect_about(pos[0]))];
+    Quantity::new(Box::new(func), vec![mid, end])
+        .with_label(format!("end opposite P{end} about midpoint P{mid}"))
+        .with_measured(vec![mid])
+}
+
+/// At the shared center of `centers`.
+///
+/// Centers which don't coincide leave no position. Coincidence is judged
+/// with the default [`Tolerance`], not [`SolveOptions::tolerance`].
+pub fn concentric(centers: Vec<PID>) -> Quantity {
+    let label = format!("concentric with {}", centers.iter().map(|c| format!("P{c}")).join(", "));
+    let func = move |pos: Vec<Vector>| {
+        let Some((&first, rest)) = pos.split_first() else {
+            return vec![];
+        };
+        if rest.iter().all(|c| c.about_eq(first)) {
+            vec![Geo::Point(first)]
+        } else {
+            vec![]
+        }
+    };
+    Quantity::new(Box::new(func), centers)
+        .with_label(label)
+        .with_measured(vec![])
+}
+
+/// As far from `end` as `a` is from `b`.
+pub fn equal_length(a: PID, b: PID, end: PID) -> Quantity {
+    ratio(a, b, end, 1.0).with_label(format!("length of P{a}-P{b} from P{end}"))
+}
+
+/// `k` times as far from `end` as `a` is from `b`.
+///
+/// Use `1.0 / k` to place an end of the reference segment instead.
+pub fn ratio(a: PID, b: PID, end: PID, k: Number) -> Quantity {
+    let func = move |pos: Vec<Vector>| vec![Geo::Circle(pos[2], pos[0].dist(pos[1]) * k)];
+    Quantity::new(Box::new(func), vec![a, b, end])
+        .with_label(format!("{k} times P{a}-P{b} from P{end}"))
+        .with_measured(vec![end])
+}
+
+/// Test figure: anchor `a` with `b` at `len` along the x axis.
+#[cfg(test)]
+pub(crate) fn base(len: Number) -> (Order, [PID; 2]) {
+    let mut fig = Order::default();
+    let a = fig.add_point(vec![]);
+    let b = fig.add_point(vec![distance(a, len), orientation(a, 0.)]);
+    (fig, [a, b])
+}
+
+/// Test figure: [`base`] of 10 with `c` at `ac` from `a` and `bc` from
+/// `b`, on either side.
+#[cfg(test)]
+pub(crate) fn triangle(ac: Number, bc: Number) -> (Order, [PID; 3]) {
+    let (mut fig, [a, b]) = base(10.);
+    let c = fig.add_point(vec![distance(a, ac), distance(b, bc)]);
+    (fig, [a, b, c])
+}
+
+/// Test figure: [`base`] of `w`, then `c` and `d` closing a `w` by `h`
+/// rectangle above it.
+#[cfg(test)]
+pub(crate) fn rectangle(w: Number, h: Number) -> (Order, [PID; 4]) {
+    let (mut fig, [a, b]) = base(w);
+    let c = fig.add_point(vec![distance(b, h), orientation(b, PI / 2.)]);
+    let d = fig.add_point(vec![distance(a, h), distance(c, w)]);
+    (fig, [a, b, c, d])
+}
+
+#[test]
+fn rect() {
+    use crate::math::consts::PI;
 
     let mut fig = Order::default();
     let a = fig.add_point(vec![]);
@@ -85,3 +1075,593 @@ fn rect() {
         println!("{}", p);
     }
 }
+
+#[test]
+fn solve_errors() {
+    let mut fig = Order::default();
+    let a = fig.add_point(vec![]);
+    fig.add_point(vec![distance(a, 1.), distance(a, 2.)]);
+    assert_eq!(fig.solve(), Err(SolveError::Contradiction { point: 1 }));
+}
+
+#[test]
+fn long_chain() {
+    let mut fig = Order::default();
+    let mut prev = fig.add_point(vec![]);
+    for _ in 0..5000 {
+        prev = fig.add_point(vec![distance(prev, 1.), orientation(prev, 0.)]);
+    }
+    let result = fig.solve().unwrap();
+    assert_eq!(result[prev], Vector { x: 5000., y: 0. });
+}
+
+#[test]
+fn mirrored_triangle() {
+    let (fig, [.., c]) = triangle(6., 8.);
+    let solutions = fig.solve_all(usize::MAX).unwrap();
+    assert_eq!(solutions.len(), 2);
+    assert!(solutions[0][c].about_eq(Vector { x: solutions[1][c].x, y: -solutions[1][c].y }));
+    assert_eq!(fig.solve_all(1).unwrap().len(), 1);
+}
+
+#[test]
+fn refine_square() {
+    let (fig, [.., d]) = rectangle(10., 10.);
+    let mut positions = vec![
+        Vector::ZERO,
+        Vector { x: 10., y: 1e-6 },
+        Vector { x: 10. + 1e-6, y: 10. },
+        Vector { x: -1e-6, y: 10. - 1e-6 },
+    ];
+    assert!(fig.residual(&positions) > 1e-13);
+    fig.refine(&mut positions, 50);
+    assert!(fig.residual(&positions) < 1e-20);
+    assert!(positions[d].dist(Vector { x: 0., y: 10. }) < 1e-10);
+}
+
+#[test]
+fn refine_first() {
+    let mut fig = Order::default();
+    let a = fig.add_point(vec![fixed(Vector { x: 1., y: 2. })]);
+    let b = fig.add_point(vec![distance(a, 5.), horizontal(a)]);
+    let mut positions = vec![Vector::ZERO, Vector { x: 6., y: 2. }];
+    fig.refine(&mut positions, 10);
+    assert!(positions[a].about_eq(Vector { x: 1., y: 2. }));
+    assert!(positions[b].about_eq(Vector { x: 6., y: 2. }));
+}
+
+#[test]
+fn drag() {
+    let (fig, [.., c]) = triangle(6., 8.);
+    let guess = vec![Vector::ZERO, Vector::ZERO, Vector { x: 4., y: -5. }];
+    let mut prev = fig.solve_from(guess).unwrap();
+    assert!(prev[c].y < 0.);
+    for k in 1..20 {
+        let (fig, _) = triangle(6. + k as Number * 0.1, 8.);
+        let next = fig.solve_from(prev.clone()).unwrap();
+        assert!(next[c].y < 0.);
+        assert!(next[c].dist(prev[c]) < 0.5);
+        prev = next;
+    }
+}
+
+#[test]
+fn budget() {
+    let (mut fig, [a, b]) = base(10.);
+    // Each of these has two mirrored positions.
+    for _ in 0..30 {
+        fig.add_point(vec![distance(a, 6.), distance(b, 8.)]);
+    }
+    fig.add_point(vec![distance(a, 1.), distance(a, 2.)]);
+    let options = SolveOptions {
+        max_nodes: 100,
+        ..Default::default()
+    };
+    assert_eq!(fig.solve_with(options), Err(SolveError::BudgetExceeded));
+    // Steps are taken one at a time, so the first few come quickly.
+    assert!(fig.solve_steps().take(10).all(|step| step.is_ok()));
+    // Even the defaults give up eventually.
+    let options = SolveOptions::default();
+    assert!(options.max_nodes < usize::MAX && options.time_limit.is_some());
+}
+
+#[test]
+fn stuck_at() {
+    let (mut fig, [a, b, _]) = triangle(6., 8.);
+    fig.add_point(vec![distance(a, 1.), distance(b, 1.)]);
+    assert_eq!(fig.solve(), Err(SolveError::StuckAt { point: 3 }));
+}
+
+#[test]
+fn tangent_line() {
+    let mut fig = Order::default();
+    let c = fig.add_point(vec![]);
+    let k = fig.add_point(vec![distance(c, 2.), orientation(c, 0.)]);
+    let p = fig.add_point(vec![tangent(c, 1., k), distance(k, 3.)]);
+    let result = fig.solve().unwrap();
+    let (dir, _) = (result[p] - result[k]).unit_mag();
+    assert!((result[c] - result[k]).cross(dir).abs().about_eq(1.));
+}
+
+#[test]
+fn midpoints() {
+    use crate::math::consts::PI;
+
+    let mut fig = Order::default();
+    let a = fig.add_point(vec![]);
+    let b = fig.add_point(vec![distance(a, 4.), orientation(a, 0.)]);
+    let m = fig.add_point(vec![midpoint(a, b)]);
+    let result = fig.solve().unwrap();
+    assert!(result[m].about_eq(Vector { x: 2., y: 0. }));
+
+    let mut fig = Order::default();
+    let a = fig.add_point(vec![]);
+    let m = fig.add_point(vec![distance(a, 2.), orientation(a, 0.)]);
+    let b = fig.add_point(vec![midpoint_end(m, a)]);
+    let result = fig.solve().unwrap();
+    assert!(result[b].about_eq(Vector { x: 4., y: 0. }));
+
+    let mut fig = Order::default();
+    let m = fig.add_point(vec![]);
+    let b = fig.add_point(vec![distance(m, 2.), orientation(m, PI / 2.)]);
+    let a = fig.add_point(vec![midpoint_end(m, b)]);
+    let result = fig.solve().unwrap();
+    assert!(result[a].about_eq(Vector { x: 0., y: -2. }));
+}
+
+#[test]
+fn concentric_centers() {
+    let mut fig = Order::default();
+    let a = fig.add_point(vec![]);
+    let b = fig.add_point(vec![concentric(vec![a])]);
+    let c = fig.add_point(vec![concentric(vec![a, b])]);
+    let result = fig.solve().unwrap();
+    assert_eq!(result[c], result[a]);
+
+    let mut fig = Order::default();
+    let a = fig.add_point(vec![]);
+    let b = fig.add_point(vec![distance(a, 1.), orientation(a, 0.)]);
+    fig.add_point(vec![concentric(vec![a, b])]);
+    assert!(fig.solve().is_err());
+}
+
+#[test]
+fn equal_lengths() {
+    let mut fig = Order::default();
+    let a = fig.add_point(vec![]);
+    let b = fig.add_point(vec![distance(a, 7.), orientation(a, 0.)]);
+    let c = fig.add_point(vec![distance(a, 3.), orientation(a, 1.)]);
+    let d = fig.add_point(vec![equal_length(a, b, c), orientation(c, 2.)]);
+    let result = fig.solve().unwrap();
+    assert!(result[c].dist(result[d]).about_eq(7.));
+}
+
+#[test]
+fn axis_aligned() {
+    let mut fig = Order::default();
+    let a = fig.add_point(vec![]);
+    let b = fig.add_point(vec![vertical(a), distance(a, 3.)]);
+    let c = fig.add_point(vec![horizontal(b), distance(b, 2.)]);
+    let result = fig.solve().unwrap();
+    assert!(result[b].x.about_eq(result[a].x));
+    assert!(result[b].dist(result[a]).about_eq(3.));
+    assert!(result[c].y.about_eq(result[b].y));
+}
+
+#[test]
+fn point_on_circle() {
+    use crate::math::consts::PI;
+
+    let mut fig = Order::default();
+    let a = fig.add_point(vec![]);
+    let c = fig.add_point(vec![distance(a, (8.0 as Number).sqrt()), orientation(a, PI / 4.)]);
+    let p = fig.add_point(vec![on_circle(c, 3.), horizontal(c)]);
+    let result = fig.solve().unwrap();
+    assert!(result[c].about_eq(Vector { x: 2., y: 2. }));
+    assert!(result[p].dist(result[c]).about_eq(3.));
+}
+
+#[test]
+fn ratios() {
+    let mut fig = Order::default();
+    let c = fig.add_point(vec![]);
+    let d = fig.add_point(vec![distance(c, 4.), orientation(c, 0.)]);
+    let a = fig.add_point(vec![distance(c, 1.), orientation(c, 2.)]);
+    let b = fig.add_point(vec![ratio(c, d, a, 2.5), orientation(a, 1.)]);
+    let e = fig.add_point(vec![ratio(a, b, c, 1. / 2.5), orientation(c, 3.)]);
+    let result = fig.solve().unwrap();
+    assert!(result[a].dist(result[b]).about_eq(10.));
+    assert!(result[c].dist(result[e]).about_eq(4.));
+}
+
+#[test]
+fn fixed_points() {
+    let at = Vector { x: 3., y: -1. };
+    let mut fig = Order::default();
+    let a = fig.add_point(vec![fixed(at)]);
+    let b = fig.add_point(vec![distance(a, 2.), horizontal(a)]);
+    let c = fig.add_point(vec![fixed(Vector::POSY)]);
+    let result = fig.solve().unwrap();
+    assert_eq!(result[a], at);
+    assert!(result[b].dist(at).about_eq(2.));
+    assert_eq!(result[c], Vector::POSY);
+}
+
+#[test]
+fn collinear_points() {
+    let mut fig = Order::default();
+    let a = fig.add_point(vec![]);
+    let b = fig.add_point(vec![distance(a, 2.), orientation(a, 0.5)]);
+    let c = fig.add_point(vec![collinear(a, b), distance(b, 3.)]);
+    let result = fig.solve().unwrap();
+    let ab = result[b] - result[a];
+    assert!(ab.cross(result[c] - result[a]).about_zero());
+    assert!(result[c].dist(result[b]).about_eq(3.));
+}
+
+#[test]
+fn relative_angle() {
+    use crate::math::consts::PI;
+
+    let q = angle_from(0, 1, 2, PI / 2.);
+    let o = Vector { x: 5., y: 5. };
+    let geos = (q.func)(vec![o, Vector::ZERO, Vector { x: 1., y: 1. }]);
+    assert_eq!(geos.len(), 2);
+    for g in geos {
+        let Geo::Ray(start, dir) = g else {
+            panic!("expected a ray")
+        };
+        assert_eq!(start, o);
+        assert!(dir.dot(Vector { x: 1., y: 1. }).about_zero());
+    }
+}
+
+#[test]
+fn chiral_triangle() {
+    for (polarity, sign) in [(Polarity::Positive, 1.), (Polarity::Negative, -1.)] {
+        let (mut fig, [a, b]) = base(10.);
+        let c = fig.add_point(vec![
+            chirality(a, b, polarity),
+            distance(a, 6.),
+            distance(b, 8.),
+        ]);
+        let solutions = fig.solve_all(usize::MAX).unwrap();
+        assert_eq!(solutions.len(), 1);
+        assert!(solutions[0][c].y * sign > 0.);
+    }
+}
+
+#[test]
+fn two_sides() {
+    use crate::math::consts::PI;
+
+    let (mut fig, [a, b]) = base(10.);
+    let c = fig.add_point(vec![distance(a, 10.), orientation(a, PI / 2.)]);
+    // Above `a` to `b` and left of `a` to `c`.
+    let d = fig.add_point(vec![
+        chirality(a, b, Polarity::Positive),
+        chirality(a, c, Polarity::Positive),
+    ]);
+    // With only regions to place it, `d` stays put if it can.
+    let inside = Vector { x: -3., y: 4. };
+    let pos = fig.solve_from(vec![Vector::ZERO, Vector::ZERO, Vector::ZERO, inside]).unwrap();
+    assert_eq!(pos[d], inside);
+    assert_eq!(fig.verify(&pos, Tolerance::default()), Ok(()));
+    let outside = Vector { x: 3., y: 4. };
+    assert!(fig.solve_from(vec![Vector::ZERO, Vector::ZERO, Vector::ZERO, outside]).is_err());
+}
+
+#[test]
+fn parallel_segments() {
+    let mut fig = Order::default();
+    let a = fig.add_point(vec![]);
+    let b = fig.add_point(vec![distance(a, 4.), orientation(a, 0.3)]);
+    let c = fig.add_point(vec![distance(a, 2.), orientation(a, 2.)]);
+    let d = fig.add_point(vec![parallel(c, a, b), distance(c, 3.)]);
+    let e = fig.add_point(vec![distance(a, 5.), orientation(a, -1.)]);
+    let f = fig.add_point(vec![parallel(e, a, b), distance(e, 1.)]);
+    let result = fig.solve().unwrap();
+    let ab = (result[b] - result[a]).unit();
+    for (p, q) in [(c, d), (e, f)] {
+        assert!(ab.cross((result[q] - result[p]).unit()).about_zero());
+    }
+}
+
+#[test]
+fn right_angle() {
+    let mut fig = Order::default();
+    let a = fig.add_point(vec![]);
+    let b = fig.add_point(vec![distance(a, 4.), orientation(a, 0.3)]);
+    let c = fig.add_point(vec![perpendicular(b, a, b), distance(b, 3.)]);
+    let result = fig.solve().unwrap();
+    assert!((result[b] - result[a]).dot(result[c] - result[b]).about_zero());
+    assert!(result[a].dist(result[c]).about_eq(5.));
+}
+
+#[test]
+fn mirrored() {
+    use crate::math::consts::PI;
+
+    let mut fig = Order::default();
+    let a = fig.add_point(vec![]);
+    let b = fig.add_point(vec![distance(a, 1.), orientation(a, PI / 2.)]);
+    let q = fig.add_point(vec![fixed(Vector { x: 3., y: 2. })]);
+    let p = fig.add_point(vec![symmetric(q, a, b)]);
+    let result = fig.solve().unwrap();
+    assert!(result[p].about_eq(Vector { x: -3., y: 2. }));
+
+    let mut fig = Order::default();
+    let a = fig.add_point(vec![]);
+    let b = fig.add_point(vec![distance(a, 1.), orientation(a, PI / 2.)]);
+    let q = fig.add_point(vec![fixed(Vector { x: 3., y: 2. })]);
+    fig.add_point(vec![symmetric(q, a, b), fixed(Vector { x: 3., y: 2. })]);
+    assert!(fig.solve().is_err());
+}
+
+#[test]
+fn unknown_point() {
+    let mut order = Order::default();
+    let a = order.add_point(vec![]);
+    let b = order.add_point(vec![distance(a, 1.)]);
+    order.add_point(vec![distance(a, 1.), distance(b, 1.)]);
+    assert_eq!(
+        order.try_add_point(vec![distance(a, 1.), distance(99, 1.)]).err(),
+        Some(ConstraintError::UnknownPoint(99))
+    );
+    assert_eq!(order.order.len(), 3);
+    assert_eq!(order.try_add_point(vec![distance(b, 2.)]), Ok(3));
+}
+
+#[test]
+fn non_finite() {
+    let mut order = Order::default();
+    let a = order.add_point(vec![]);
+    order.add_point(vec![distance(a, Number::NAN)]);
+    assert_eq!(order.solve(), Err(SolveError::NonFinite { point: 1 }));
+}
+
+#[test]
+fn thales() {
+    use crate::math::consts::PI;
+
+    let (mut fig, [a, b]) = base(10.);
+    let v = fig.add_point(vec![inscribed_angle(a, b, PI / 2.), distance(a, 6.)]);
+    let pos = fig.solve().unwrap();
+    assert!(pos[v].dist(Vector { x: 5., y: 0. }).about_eq(5.));
+    assert!((pos[a] - pos[v]).dot(pos[b] - pos[v]).about_zero());
+    assert!(pos[v].dist(pos[b]).about_eq(8.));
+}
+
+#[test]
+fn inscribed_sixty() {
+    use crate::math::consts::PI;
+
+    let (mut fig, [a, b]) = base(10.);
+    let v = fig.add_point(vec![inscribed_angle(a, b, PI / 3.), distance(a, 8.)]);
+    let solutions = fig.solve_all(10).unwrap();
+    // One above and one below the chord, none seeing the chord at 120°.
+    assert_eq!(solutions.len(), 2);
+    for pos in solutions {
+        let (va, vb) = (pos[a] - pos[v], pos[b] - pos[v]);
+        assert!((va.dot(vb) / (va.mag() * vb.mag())).about_eq(0.5));
+    }
+
+    let (mut fig, [a, b]) = base(10.);
+    fig.add_point(vec![inscribed_angle(a, b, PI), distance(a, 5.)]);
+    assert_eq!(fig.solve(), Err(SolveError::StuckAt { point: 2 }));
+}
+
+#[test]
+fn disconnected() {
+    let (mut fig, [_, b, c]) = triangle(6., 8.);
+    // A second triangle sharing no points with the first.
+    let d = fig.add_point(vec![]);
+    let e = fig.add_point(vec![distance(d, 3.), orientation(d, 0.)]);
+    let f = fig.add_point(vec![distance(d, 4.), distance(e, 5.)]);
+    let pos = fig.solve().unwrap();
+    assert_eq!(pos[d], Vector::ZERO);
+    assert!(pos[c].dist(pos[b]).about_eq(8.));
+    assert!(pos[f].dist(pos[d]).about_eq(4.));
+    assert!(pos[f].dist(pos[e]).about_eq(5.));
+}
+
+#[test]
+fn independent_failure() {
+    let (mut fig, [a, b]) = base(10.);
+    // Each of these has two mirrored positions.
+    for _ in 0..30 {
+        fig.add_point(vec![distance(a, 6.), distance(b, 8.)]);
+    }
+    // A separate triangle that can't close, so no choice above helps it.
+    let d = fig.add_point(vec![]);
+    let e = fig.add_point(vec![distance(d, 1.), orientation(d, 0.)]);
+    let f = fig.add_point(vec![distance(d, 1.), distance(e, 5.)]);
+    let options = SolveOptions {
+        max_nodes: 100,
+        ..Default::default()
+    };
+    assert_eq!(fig.solve_with(options), Err(SolveError::StuckAt { point: f }));
+    assert_eq!(fig.solve_all(4), Ok(vec![]));
+}
+
+#[test]
+fn deterministic() {
+    let (mut fig, [_, b, c]) = triangle(6., 8.);
+    fig.add_point(vec![distance(b, 5.), distance(c, 5.)]);
+    let bits = |pos: Vec<Vector>| -> Vec<_> {
+        pos.iter().map(|p| (p.x.to_bits(), p.y.to_bits())).collect()
+    };
+    assert_eq!(bits(fig.solve().unwrap()), bits(fig.solve().unwrap()));
+}
+
+#[test]
+fn possibility_space() {
+    let (fig, [a, b, c]) = triangle(6., 8.);
+    let positions = [Vector::ZERO, Vector { x: 10., y: 0. }, Vector::ZERO];
+    let space = fig.possibility_space(&positions, c);
+    assert_eq!(space.len(), 2);
+    for g in space {
+        let Geo::Point(p) = g else {
+            panic!("expected a point")
+        };
+        assert!(p.dist(positions[a]).about_eq(6.));
+        assert!(p.dist(positions[b]).about_eq(8.));
+    }
+}
+
+#[cfg(feature = "rayon")]
+#[test]
+fn parallel_solve() {
+    let (mut fig, [a, b, c]) = triangle(6., 8.);
+    fig.add_point(vec![distance(b, 5.), distance(c, 5.)]);
+    let solution = fig.solve_parallel().unwrap();
+    assert_eq!(Ok(solution), fig.solve());
+
+    fig.add_point(vec![distance(a, 1.), distance(b, 1.)]);
+    assert_eq!(fig.solve_parallel(), Err(SolveError::StuckAt { point: 4 }));
+    assert_eq!(fig.solve_parallel(), fig.solve());
+
+    // Stuck before any choice, the same as the serial search.
+    let (mut fig, [a, b]) = base(10.);
+    fig.add_point(vec![distance(a, 1.), distance(b, 1.)]);
+    assert_eq!(fig.solve(), Err(SolveError::StuckAt { point: 2 }));
+    assert_eq!(fig.solve_parallel(), fig.solve());
+}
+
+#[cfg(not(feature = "rayon"))]
+#[test]
+fn local_quantity() {
+    use std::rc::Rc;
+
+    // Without `rayon`, quantities needn't be shareable across threads.
+    let at = Rc::new(Vector::POSX);
+    let mut fig = Order::default();
+    let p = fig.add_point(vec![Quantity::new(Box::new(move |_| vec![Geo::Point(*at)]), vec![])]);
+    assert_eq!(fig.solve().unwrap()[p], Vector::POSX);
+}
+
+#[test]
+fn custom_quantity() {
+    let (mut fig, [a, b]) = base(10.);
+    let func = |pos: Vec<Vector>| vec![Geo::Point(pos[0].midpoint(pos[1]))];
+    let q = Quantity::new(Box::new(func), vec![a, b]);
+    assert_eq!((q.label.as_str(), &q.measured), ("quantity on P0, P1", &vec![a, b]));
+    let m = fig.add_point(vec![q.with_label("between").with_measured(vec![a])]);
+    assert_eq!(fig.describe_order()[m], "P2: between");
+    assert!(fig.solve().unwrap()[m].about_eq(Vector { x: 5., y: 0. }));
+}
+
+#[test]
+fn nearest() {
+    let (mut fig, [_, b, c]) = triangle(6., 8.);
+    let d = fig.add_point(vec![distance(b, 5.), distance(c, 5.)]);
+    for y in [-5., 5.] {
+        let initial = [Vector::ZERO, Vector { x: 10., y: 0. }, Vector { x: 4., y }, Vector::ZERO];
+        let pos = fig.solve_nearest(&initial).unwrap();
+        assert_eq!(pos[c].y.signum(), y.signum());
+        assert!(pos[d].dist(pos[c]).about_eq(5.));
+    }
+}
+
+#[test]
+fn verify_square() {
+    let (fig, [.., c, d]) = rectangle(10., 10.);
+    let mut pos = fig.solve().unwrap();
+    assert_eq!(fig.verify(&pos, Tolerance::default()), Ok(()));
+
+    pos[c].x += 0.5;
+    let broken = fig.verify(&pos, Tolerance::default()).unwrap_err();
+    let ids: Vec<QID> = broken.iter().map(|&(id, _)| id).collect();
+    assert_eq!(ids, vec![(c, 0), (c, 1), (d, 1)]);
+    assert!(broken[1].1.about_eq(0.5));
+}
+
+#[test]
+fn orientation_from_segment() {
+    let q = orientation_parallel(0, 1, 2);
+    let o = Vector { x: 5., y: 5. };
+    let geos = (q.func)(vec![o, Vector::ZERO, Vector { x: 3., y: 4. }]);
+    assert_eq!(geos, vec![Geo::Ray(o, Vector { x: 0.6, y: 0.8 })]);
+}
+
+#[test]
+fn residual_vector() {
+    let (fig, _) = rectangle(10., 10.);
+    let pos = fig.solve().unwrap();
+    let residuals = fig.residual_vector(&pos);
+    assert_eq!(residuals.len(), fig.residual_dim());
+    assert_eq!(residuals.len(), 6);
+    assert!(residuals.iter().all(|r| r.about_zero()));
+}
+
+#[test]
+fn describe_rect() {
+    let (mut fig, [a, b]) = base(10.);
+    let c = fig.add_point(vec![distance(b, 5.), vertical(b), chirality(a, b, Polarity::Positive)]);
+    fig.add_point(vec![distance(a, 5.), distance(c, 10.)]);
+    assert_eq!(
+        fig.describe_order(),
+        vec![
+            "P0: anchor",
+            "P1: distance 10 from P0 ∩ orientation 0 from P0",
+            "P2: distance 5 from P1 ∩ above or below P1 ∩ left of P0-P1",
+            "P3: distance 5 from P0 ∩ distance 10 from P2",
+        ]
+    );
+}
+
+#[test]
+fn least_squares() {
+    let (mut fig, [a, b]) = base(10.);
+    // Too short to reach each other, so the distances are 2 apart in total.
+    let c = fig.add_point(vec![distance(a, 5.), distance(b, 3.)]);
+    assert!(fig.solve().is_err());
+
+    let pos = fig.solve_least_squares(&HashMap::new(), 50);
+    assert!(pos[c].about_eq(Vector { x: 6., y: 0. }));
+    let weights = HashMap::from([((c, 0), 3.)]);
+    let pos = fig.solve_least_squares(&weights, 50);
+    assert!(pos[c].about_eq(Vector { x: 5.5, y: 0. }));
+}
+
+#[test]
+fn measured() {
+    use crate::math::consts::PI;
+
+    let (fig, [a, b, c, d]) = rectangle(10., 5.);
+    let pos = fig.solve().unwrap();
+    assert!(measured_distance(&pos, a, b).about_eq(10.));
+    assert!(measured_distance(&pos, b, c).about_eq(5.));
+    assert!(measured_orientation(&pos, b, c).about_eq(PI / 2.));
+    assert!(measured_orientation(&pos, c, d).abs().about_eq(PI));
+}
+
+#[test]
+fn steps() {
+    let (mut fig, [a, b, c]) = triangle(6., 8.);
+    // Only solvable with the second choice for `c`.
+    fig.add_point(vec![distance(c, 1.), vertical(c), chirality(a, b, Polarity::Negative)]);
+    let solution = fig.solve().unwrap();
+    let mut replay = vec![Vector::ZERO; solution.len()];
+    let mut count = 0;
+    for step in fig.solve_steps() {
+        let (i, p) = step.unwrap();
+        replay[i] = p;
+        count += 1;
+    }
+    // Point 0 is never placed, and backtracking placed one point twice.
+    assert!(count > solution.len() - 1);
+    assert_eq!(replay, solution);
+
+    // A failed solve ends with its error.
+    fig.add_point(vec![distance(a, 1.), distance(b, 1.)]);
+    let last = fig.solve_steps().last();
+    assert_eq!(last, Some(Err(SolveError::StuckAt { point: 4 })));
+}
+
+#[test]
+fn old_id_paths() {
+    let pid: crate::order::PID = 0;
+    let qid: crate::order::QID = (pid, 0);
+    assert_eq!(qid, (crate::PID::default(), 0));
+}
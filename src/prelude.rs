@@ -0,0 +1,24 @@
+//! The common types and quantities in one import.
+//!
+//! ```
+//! # #[cfg(feature = "std")] {
+//! use gsolve::prelude::*;
+//!
+//! let mut fig = Order::default();
+//! let a = fig.add_point(vec![]);
+//! let b = fig.add_point(vec![distance(a, 10.), orientation(a, 0.)]);
+//! let c = fig.add_point(vec![distance(a, 6.), distance(b, 8.)]);
+//! let positions = fig.solve().unwrap();
+//! assert!(positions[c].dist(positions[b]).about_eq(8.));
+//! # }
+//! ```
+
+pub use crate::math::{AboutEq, Geo, Number, Tolerance, Vector};
+#[cfg(feature = "std")]
+pub use crate::order::{
+    ConstraintError, Order, Polarity, Quantity, SolveError, SolveOptions, angle_from, chirality,
+    collinear, concentric, distance, equal_length, fixed, horizontal, inscribed_angle, midpoint,
+    midpoint_end, on_circle, orientation, orientation_parallel, parallel, perpendicular, ratio,
+    symmetric, tangent, vertical,
+};
+pub use crate::{PID, QID};